@@ -2,8 +2,9 @@ use crate::{
     cli::CliArgs,
     error::{Result as TextGenResult, TextGenError},
     model::{Generator, NGramModel},
-    output::insights::show_text_insights,
-    output::wordcloud::generate_wordcloud,
+    output::insights::show_text_insights_with,
+    output::wordcloud::generate_wordcloud_with,
+    text::{Detokenizer, LanguageProfile, Stemmer, StemmingLanguage, VocabularyFilter},
 };
 
 use log::info;
@@ -34,28 +35,35 @@ pub async fn run_app(args: CliArgs) -> TextGenResult<()> {
         display_model_stats(&model);
     }
 
-    let generated_text = generate_text(&model, &args);
+    if let Some(path) = &args.save_model {
+        info!("Saving model to {}", path.display());
+        model.save(path)?;
+    }
+
+    let generated_text = generate_text(&model, &args)?;
 
     if let Some(output_file) = &args.output_file {
         info!("Saving generated text to {}", output_file.display());
         let mut file = fs::File::create(output_file)
             .await
-            .map_err(|e| TextGenError::Io(e))?;
+            .map_err(TextGenError::Io)?;
         file.write_all(generated_text.as_bytes())
             .await
-            .map_err(|e| TextGenError::Io(e))?;
+            .map_err(TextGenError::Io)?;
         info!("Generated text saved successfully");
     } else {
         println!("\nGenerated text:\n{}", generated_text);
     }
 
+    let vocabulary = build_vocabulary_filter(&args)?;
+
     if !args.no_insights {
-        show_text_insights(&generated_text);
+        show_text_insights_with(&generated_text, &vocabulary);
     }
 
     if !args.no_wordcloud {
         info!("Generating wordcloud from the text...");
-        let wordcloud_path = generate_wordcloud(&generated_text)?;
+        let wordcloud_path = generate_wordcloud_with(&generated_text, &vocabulary)?;
         info!("Wordcloud saved to {}", wordcloud_path);
     }
 
@@ -63,6 +71,28 @@ pub async fn run_app(args: CliArgs) -> TextGenResult<()> {
     Ok(())
 }
 
+fn build_vocabulary_filter(args: &CliArgs) -> TextGenResult<VocabularyFilter> {
+    let mut vocabulary = VocabularyFilter::new().with_min_length(args.min_word_length);
+
+    for language in &args.stopwords {
+        vocabulary = vocabulary.with_stopwords(language)?;
+    }
+    if let Some(path) = &args.stopwords_file {
+        vocabulary = vocabulary.with_stopword_file(path)?;
+    }
+    if let Some(max_length) = args.max_word_length {
+        vocabulary = vocabulary.with_max_length(max_length);
+    }
+    if let Some(pattern) = &args.exclude_words {
+        vocabulary = vocabulary.with_exclude_pattern(pattern)?;
+    }
+    if let Some(language) = &args.insights_stem {
+        vocabulary = vocabulary.with_stemmer(Stemmer::new(StemmingLanguage::from_code(language)?));
+    }
+
+    Ok(vocabulary)
+}
+
 fn display_model_stats(model: &NGramModel) {
     let stats = model.get_stats();
     println!("\nModel Statistics:");
@@ -81,12 +111,20 @@ fn display_model_stats(model: &NGramModel) {
     );
 }
 
-fn generate_text(model: &NGramModel, args: &CliArgs) -> String {
+fn generate_text(model: &NGramModel, args: &CliArgs) -> TextGenResult<String> {
     info!("Generating text (length: {} tokens)", args.length);
-    let generator = Generator::new(model);
+    let profile = LanguageProfile::from_code(&args.output_language).ok_or_else(|| {
+        TextGenError::Config(format!(
+            "Unsupported output language '{}'",
+            args.output_language
+        ))
+    })?;
+    let generator = Generator::new(model)
+        .with_detokenizer(Detokenizer::new(profile).with_headings(!args.no_headings))
+        .with_truecasing(!args.no_truecase);
     let seed_words = args
         .seed
         .as_ref()
         .map(|s| s.split_whitespace().map(String::from).collect());
-    generator.generate(seed_words, args.length)
+    Ok(generator.generate(seed_words, args.length))
 }
@@ -2,66 +2,338 @@ use crate::{
     cli::CliArgs,
     config::load_config,
     error::{Result as TextGenResult, TextGenError},
+    input::{
+        Compression, FieldFilter, InputDocument, InputFormat, ReadOptions, RecordSelector,
+        collect_files, compression, formats, read_documents,
+    },
     model::{NGramModel, Trainer},
-    scrapers::{Scraper, get_scraper_interactive, load_scraper_from_config},
-    text::TokenizerOptions,
+    scrapers::{
+        ResponseCache, cache, configure_interactive, join_documents, load_scraper_from_config,
+    },
+    text::{
+        CleaningPipeline, DedupOptions, Deduplicator, LanguageDetector, LanguageReport,
+        StemmingLanguage, TokenizerOptions, language::validate_languages,
+    },
 };
 
 use chrono::Utc;
 use log::{debug, info, warn};
-use tokio::fs;
+use serde_json::json;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use super::utils::ask_user;
 
-pub async fn train_new_model(args: &CliArgs) -> TextGenResult<NGramModel> {
-    let source_text = if let Some(input_file) = &args.input_file {
-        load_text_from_file(input_file).await?
-    } else {
-        fetch_text_from_scraper(args).await?
-    };
+/// `--input-file` value that reads training text from standard input
+const STDIN_PATH: &str = "-";
 
+pub async fn train_new_model(args: &CliArgs) -> TextGenResult<NGramModel> {
     let tokenizer_options = TokenizerOptions {
         lowercase: args.lowercase,
         preserve_punctuation: args.preserve_punctuation,
         preserve_sentence_boundaries: args.preserve_sentence_boundaries,
+        preserve_paragraphs: args.preserve_paragraphs,
+        stemming: args
+            .stem
+            .as_deref()
+            .map(StemmingLanguage::from_code)
+            .transpose()?,
+    };
+    let trainer = Trainer::new(args.n).with_tokenizer_options(tokenizer_options.clone());
+    let mut model = NGramModel::new(args.n);
+
+    let token_counts = match streaming_sources(args)? {
+        Some(sources) => {
+            log_training_start(args, &tokenizer_options);
+            tokio::task::block_in_place(|| stream_sources(&trainer, &mut model, &sources))?
+        }
+        None => {
+            let documents = load_documents(args).await?;
+            log_training_start(args, &tokenizer_options);
+
+            let mut token_counts = Vec::with_capacity(documents.len());
+            for document in documents {
+                let count = trainer.add_document(&mut model, &document.text)?;
+                token_counts.push((document.source, count));
+            }
+            token_counts
+        }
     };
+    model.update_stats();
+    info!("Model training complete");
+
+    if args.show_stats && token_counts.len() > 1 {
+        display_document_stats(&token_counts);
+    }
+
+    Ok(model)
+}
 
+fn log_training_start(args: &CliArgs, tokenizer_options: &TokenizerOptions) {
     info!("Training model with n-gram size: {}", args.n);
     debug!(
-        "Tokenizer options: lowercase={}, preserve_punctuation={}, preserve_sentence_boundaries={}",
+        "Tokenizer options: lowercase={}, preserve_punctuation={}, preserve_sentence_boundaries={}, preserve_paragraphs={}, stemming={:?}",
         tokenizer_options.lowercase,
         tokenizer_options.preserve_punctuation,
-        tokenizer_options.preserve_sentence_boundaries
+        tokenizer_options.preserve_sentence_boundaries,
+        tokenizer_options.preserve_paragraphs,
+        tokenizer_options.stemming
     );
+}
 
-    let trainer = Trainer::new(args.n).with_tokenizer_options(tokenizer_options);
-    let model = trainer.train_from_text(&source_text)?;
-    info!("Model training complete");
+/// Files that can be streamed straight into the trainer
+///
+/// Language filtering and deduplication need whole documents, so streaming is
+/// only used without them, and never for scraped text.
+fn streaming_sources(args: &CliArgs) -> TextGenResult<Option<Vec<PathBuf>>> {
+    if args.detect_language || !args.languages.is_empty() || args.dedup {
+        return Ok(None);
+    }
 
-    Ok(model)
+    let format = InputFormat::from_name(&args.input_format)?;
+    let sources = if let Some(input_file) = &args.input_file {
+        vec![input_file.clone()]
+    } else if args.input_dir.is_some() || args.input_glob.is_some() {
+        find_input_files(args)?
+    } else {
+        return Ok(None);
+    };
+
+    // Structured formats are converted to text as a whole
+    let plain_text = sources.iter().all(|source| {
+        source.as_os_str() == STDIN_PATH || format.resolve(source) == InputFormat::Text
+    });
+    Ok(plain_text.then_some(sources))
+}
+
+fn stream_sources(
+    trainer: &Trainer,
+    model: &mut NGramModel,
+    sources: &[PathBuf],
+) -> TextGenResult<Vec<(String, usize)>> {
+    let mut token_counts = Vec::with_capacity(sources.len());
+
+    for source in sources {
+        let (name, count) = if source.as_os_str() == STDIN_PATH {
+            info!("Streaming source text from standard input");
+            let reader = compression::decompress(BufReader::new(std::io::stdin()))?;
+            ("stdin".to_string(), trainer.add_reader(model, reader)?)
+        } else {
+            info!("Streaming source text from file: {}", source.display());
+            let reader = compression::open(source)?;
+            (
+                source.display().to_string(),
+                trainer.add_reader(model, reader)?,
+            )
+        };
+        debug!("{}: {} tokens", name, count);
+        token_counts.push((name, count));
+    }
+
+    Ok(token_counts)
+}
+
+async fn load_documents(args: &CliArgs) -> TextGenResult<Vec<InputDocument>> {
+    let options = read_options(args)?;
+
+    let documents = if let Some(input_file) = &args.input_file {
+        if input_file.as_os_str() == STDIN_PATH {
+            load_documents_from_stdin(options)?
+        } else {
+            info!("Loading source text from file: {}", input_file.display());
+            read_documents(std::slice::from_ref(input_file), &options).await?
+        }
+    } else if args.input_dir.is_some() || args.input_glob.is_some() {
+        let files = find_input_files(args)?;
+        info!("Loading source text from {} files", files.len());
+        read_documents(&files, &options).await?
+    } else {
+        fetch_documents_from_scraper(args).await?
+    };
+
+    let documents = if args.detect_language || !args.languages.is_empty() {
+        filter_languages(documents, args)?
+    } else {
+        documents
+    };
+
+    let documents = if args.dedup {
+        deduplicate(documents, args)
+    } else {
+        documents
+    };
+
+    Ok(documents)
 }
 
-async fn load_text_from_file(input_file: &std::path::Path) -> TextGenResult<String> {
-    info!("Loading source text from file: {}", input_file.display());
-    fs::read_to_string(input_file)
-        .await
-        .map_err(|e| TextGenError::Io(e))
+fn display_document_stats(token_counts: &[(String, usize)]) {
+    println!("\nTraining Documents:");
+    println!("-------------------");
+    for (source, count) in token_counts {
+        println!("{}: {} tokens", source, count);
+    }
+    println!(
+        "Total: {} tokens in {} documents",
+        token_counts.iter().map(|(_, count)| count).sum::<usize>(),
+        token_counts.len()
+    );
 }
 
-async fn fetch_text_from_scraper(args: &CliArgs) -> TextGenResult<String> {
-    let scraper: Box<dyn Scraper> = if let Some(config_path) = &args.scraper_config {
+fn filter_languages(
+    documents: Vec<InputDocument>,
+    args: &CliArgs,
+) -> TextGenResult<Vec<InputDocument>> {
+    let detector = LanguageDetector::default();
+    validate_languages(&detector, &args.languages)?;
+
+    let mut labeled_all = Vec::new();
+    let mut kept = Vec::with_capacity(documents.len());
+
+    for document in documents {
+        let labeled = detector.label_documents(&document.text);
+        for section in &labeled {
+            debug!(
+                "{} {:?} detected as {}",
+                document.source,
+                section.heading.as_deref().unwrap_or("(untitled)"),
+                section.language.as_deref().unwrap_or("unknown")
+            );
+        }
+
+        if args.languages.is_empty() {
+            kept.push(document);
+        } else {
+            let text: Vec<&str> = labeled
+                .iter()
+                .filter(|section| section.is_in(&args.languages))
+                .map(|section| section.text.as_str())
+                .collect();
+            if !text.is_empty() {
+                kept.push(InputDocument::new(document.source, text.join("\n\n")));
+            }
+        }
+        labeled_all.extend(labeled);
+    }
+
+    info!(
+        "Language mix: {}",
+        LanguageReport::from_documents(&labeled_all)
+    );
+
+    if !args.languages.is_empty() {
+        info!(
+            "Kept {} documents in languages: {}",
+            kept.len(),
+            args.languages.join(", ")
+        );
+        if kept.iter().all(|document| document.text.trim().is_empty()) {
+            return Err(TextGenError::Config(format!(
+                "No text left after keeping only languages: {}",
+                args.languages.join(", ")
+            )));
+        }
+    }
+
+    Ok(kept)
+}
+
+fn deduplicate(documents: Vec<InputDocument>, args: &CliArgs) -> Vec<InputDocument> {
+    info!(
+        "Removing duplicates (similarity threshold: {})...",
+        args.dedup_threshold
+    );
+    let deduplicator = Deduplicator::new(DedupOptions {
+        threshold: args.dedup_threshold,
+        collapse_repeats: args.collapse_repeats,
+        ..Default::default()
+    });
+
+    let texts: Vec<&str> = documents.iter().map(|d| d.text.as_str()).collect();
+    let (deduplicated, report) = deduplicator.dedup_many(&texts);
+    info!("Deduplication {}", report);
+
+    documents
+        .into_iter()
+        .zip(deduplicated)
+        .map(|(document, text)| InputDocument::new(document.source, text))
+        .collect()
+}
+
+fn find_input_files(args: &CliArgs) -> TextGenResult<Vec<PathBuf>> {
+    let files = collect_files(
+        args.input_dir.as_deref(),
+        args.input_glob.as_deref(),
+        &args.extensions,
+    )?;
+    if files.is_empty() {
+        return Err(TextGenError::Config(format!(
+            "No input files with extensions {} found",
+            args.extensions.join(", ")
+        )));
+    }
+    Ok(files)
+}
+
+fn read_options(args: &CliArgs) -> TextGenResult<ReadOptions> {
+    let mut records = RecordSelector::new(&args.text_field);
+    if let Some(condition) = &args.record_filter {
+        records = records.with_filter(FieldFilter::parse(condition)?);
+    }
+
+    Ok(ReadOptions::new(InputFormat::from_name(&args.input_format)?).with_records(records))
+}
+
+fn load_documents_from_stdin(options: ReadOptions) -> TextGenResult<Vec<InputDocument>> {
+    info!("Reading source text from standard input");
+    let bytes = tokio::task::block_in_place(|| {
+        let mut bytes = Vec::new();
+        compression::decompress(BufReader::new(std::io::stdin()))?
+            .read_to_end(&mut bytes)
+            .map_err(TextGenError::Io)?;
+        Ok::<_, TextGenError>(bytes)
+    })?;
+    info!("Read {} bytes from standard input", bytes.len());
+
+    let options = match options.format {
+        InputFormat::Auto => ReadOptions {
+            format: InputFormat::Text,
+            ..options
+        },
+        _ => options,
+    };
+    formats::parse(bytes, "stdin", &options)
+}
+
+async fn fetch_documents_from_scraper(args: &CliArgs) -> TextGenResult<Vec<InputDocument>> {
+    // Scrapers pick up the installed cache when they are created
+    if !args.no_cache {
+        if args.offline {
+            info!(
+                "Offline mode: serving requests from {}",
+                args.cache_dir.display()
+            );
+        }
+        cache::install(
+            ResponseCache::new(&args.cache_dir)
+                .with_ttl(Duration::from_secs(args.cache_ttl.saturating_mul(60 * 60)))
+                .with_offline(args.offline),
+        );
+    }
+
+    let mut config = if let Some(config_path) = &args.scraper_config {
         info!(
             "Loading scraper configuration from: {}",
             config_path.display()
         );
         match load_config(config_path) {
-            Ok(config) => load_scraper_from_config(&config)?,
+            Ok(config) => config,
             Err(e) => {
                 warn!(
                     "Error loading config file: {}. Using interactive config instead.",
                     e
                 );
-                get_scraper_interactive(&args.scraper_type)?
+                configure_interactive(&args.scraper_type)?
             }
         }
     } else {
@@ -69,26 +341,69 @@ async fn fetch_text_from_scraper(args: &CliArgs) -> TextGenResult<String> {
             "Using interactive configuration for scraper type: {}",
             args.scraper_type
         );
-        get_scraper_interactive(&args.scraper_type)?
+        configure_interactive(&args.scraper_type)?
+    };
+
+    if args.ignore_robots {
+        config.settings["ignore_robots"] = json!(true);
+    }
+
+    let scraper = load_scraper_from_config(&config)?;
+    let cleaning = if args.no_clean {
+        CleaningPipeline::empty()
+    } else {
+        CleaningPipeline::from_settings(&config.settings)?
     };
 
     info!("Fetching text using scraper...");
-    let scraped_text = scraper.fetch_text().await?;
+    let scraped = scraper.fetch_documents().await?;
+    let scraped_chars: usize = scraped.iter().map(|document| document.text.len()).sum();
     info!(
-        "Successfully fetched {} characters of text",
-        scraped_text.len()
+        "Successfully fetched {} documents ({} characters of text)",
+        scraped.len(),
+        scraped_chars
     );
 
     if ask_user("Do you want to save scraped data? (y/n): ") {
         let timestamp = Utc::now().format("%Y%m%d%H%M%S").to_string();
-        let filename = format!("scraped_data_{}.txt", timestamp);
+        let compression = Compression::from_name(&args.compress_dumps)?;
+        let filename = compression.apply_extension(format!("scraped_data_{}.txt", timestamp));
 
-        info!("Saving scraped data to {}", filename);
-        fs::write(&filename, &scraped_text)
-            .await
-            .map_err(|e| TextGenError::Io(e))?;
+        info!("Saving scraped data to {}", filename.display());
+        compression::write(&filename, join_documents(&scraped).as_bytes(), compression)?;
         info!("Scraped data saved successfully");
     }
 
-    Ok(scraped_text)
+    if !cleaning.is_empty() {
+        debug!(
+            "Cleaning scraped text with filters: {:?}",
+            cleaning.filter_names()
+        );
+    }
+
+    let mut cleaned_chars = 0;
+    let documents: Vec<InputDocument> = scraped
+        .into_iter()
+        .map(|document| {
+            let heading = document.heading();
+            let source = document.source.unwrap_or_else(|| "scraper".to_string());
+            let text = if cleaning.is_empty() {
+                document.text
+            } else {
+                cleaning.clean(&document.text)
+            };
+            cleaned_chars += text.len();
+            InputDocument::new(source, text).with_title(heading)
+        })
+        .collect();
+
+    if !cleaning.is_empty() {
+        info!(
+            "Cleaning removed {} of {} characters",
+            scraped_chars.saturating_sub(cleaned_chars),
+            scraped_chars
+        );
+    }
+
+    Ok(documents)
 }
@@ -1,7 +1,16 @@
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 
+/// Asks a yes/no question on the terminal
+///
+/// When stdin is not a terminal (piped input, `--input-file -`), nothing is
+/// read and the answer is "no".
 pub fn ask_user(prompt: &str) -> bool {
     print!("{}", prompt);
+
+    if !io::stdin().is_terminal() {
+        println!("n (non-interactive)");
+        return false;
+    }
     io::stdout().flush().unwrap();
 
     let mut response = String::new();
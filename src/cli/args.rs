@@ -1,3 +1,4 @@
+use crate::scrapers::cache::DEFAULT_CACHE_DIR;
 use clap::Parser;
 use std::path::PathBuf;
 #[derive(Parser, Debug)]
@@ -9,6 +10,8 @@ pub struct CliArgs {
     ///   - "basic": Scrapes text from a website using a CSS selector
     ///   - "wikipedia": Fetches summaries from Wikipedia for specified topics
     ///   - "lyrics": Fetches song lyrics from Genius for specified artists
+    ///   - "feed": Fetches entries from RSS and Atom feeds
+    ///   - "gutenberg": Reads Project Gutenberg books, split into chapters
     #[arg(short = 't', long, default_value = "basic")]
     pub scraper_type: String,
 
@@ -38,6 +41,16 @@ pub struct CliArgs {
     #[arg(short, long)]
     pub output_file: Option<PathBuf>,
 
+    /// Path to save the trained model as JSON
+    ///
+    /// The model is compressed when the path ends in ".gz", ".zst" or ".bz2".
+    #[arg(long)]
+    pub save_model: Option<PathBuf>,
+
+    /// Compression for saved scraped data: none, gzip, zstd or bzip2
+    #[arg(long, default_value = "none")]
+    pub compress_dumps: String,
+
     /// Seed text to start generation
     ///
     /// If provided, generation will start with these words.
@@ -45,18 +58,125 @@ pub struct CliArgs {
     #[arg(long)]
     pub seed: Option<String>,
 
+    /// Language whose punctuation rules are used to format generated text
+    ///
+    /// Available options: "en", "fr", "es".
+    #[arg(long, default_value = "en")]
+    pub output_language: String,
+
     /// Path to a local text file to use as training data
     ///
     /// If provided, this file will be used instead of scraping text.
+    /// Use "-" to read from standard input, e.g. `zcat corpus.gz | text-gen-ngram -i -`.
     #[arg(short = 'i', long)]
     pub input_file: Option<PathBuf>,
 
+    /// Directory of text files to train on, searched recursively
+    ///
+    /// Each file is trained as a separate document, so n-grams never span two files.
+    /// Files compressed with gzip, zstd or bzip2 are decompressed transparently.
+    #[arg(long)]
+    pub input_dir: Option<PathBuf>,
+
+    /// Glob pattern of files to train on
+    ///
+    /// Example: --input-glob "corpus/**/*.txt"
+    #[arg(long)]
+    pub input_glob: Option<String>,
+
+    /// Format of input files: auto, text, html, markdown, epub, subtitles, csv or jsonl
+    ///
+    /// "auto" picks the format from each file's extension. HTML, Markdown and
+    /// EPUB are converted to prose, keeping titles and headings as sections.
+    /// SRT and WebVTT subtitles are reduced to one utterance per line. Each CSV
+    /// row or JSON Lines record becomes a separate document.
+    #[arg(long, default_value = "auto")]
+    pub input_format: String,
+
+    /// CSV column, JSON key or JSON pointer (e.g. /post/body) holding the text of each record
+    #[arg(long, default_value = "text")]
+    pub text_field: String,
+
+    /// Only train on CSV/JSON Lines records matching a condition, e.g. 'author == "x"'
+    #[arg(long = "where", value_name = "CONDITION")]
+    pub record_filter: Option<String>,
+
+    /// File extensions read from --input-dir and --input-glob (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "txt")]
+    pub extensions: Vec<String>,
+
+    /// Directory where scraped HTTP responses are cached
+    #[arg(long, default_value = DEFAULT_CACHE_DIR)]
+    pub cache_dir: PathBuf,
+
+    /// Hours a cached response is used before it is revalidated with the server
+    #[arg(long, default_value_t = 24)]
+    pub cache_ttl: u64,
+
+    /// Do not read or write the HTTP cache
+    #[arg(long, conflicts_with = "offline")]
+    pub no_cache: bool,
+
+    /// Only serve scraper requests from the HTTP cache, never from the network
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Crawl without checking robots.txt or honouring its Crawl-delay
+    ///
+    /// Only for sites you own or have permission to crawl. Same as setting
+    /// `ignore_robots` in the scraper config.
+    #[arg(long)]
+    pub ignore_robots: bool,
+
+    /// Disable cleaning of scraped text
+    ///
+    /// By default, citation markers, URLs, song section tags, boilerplate and
+    /// non-text characters are removed before training. Filters (including the
+    /// opt-in "repeated_lines") can be chosen with the "cleaning" section of the
+    /// scraper config.
+    #[arg(long)]
+    pub no_clean: bool,
+
+    /// Remove duplicate and near-duplicate documents, paragraphs and lines before training
+    ///
+    /// Useful for lyrics with repeated choruses and overlapping Wikipedia extracts.
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// Similarity (0.0-1.0) above which two pieces of text count as duplicates
+    #[arg(long, default_value_t = 0.8, value_parser = parse_fraction)]
+    pub dedup_threshold: f64,
+
+    /// Collapse repetition inside a single document (e.g. a song's chorus) to one copy
+    ///
+    /// Only takes effect together with --dedup.
+    #[arg(long)]
+    pub collapse_repeats: bool,
+
+    /// Report the language mix of the training text, per `#`-headed document
+    #[arg(long)]
+    pub detect_language: bool,
+
+    /// Only train on documents detected as one of these languages (comma-separated)
+    ///
+    /// Available languages: en, fr, es, de, it, pt, fi, ru. Implies --detect-language.
+    /// Example: --languages en,fr
+    #[arg(long, value_delimiter = ',')]
+    pub languages: Vec<String>,
+
     /// Whether to convert text to lowercase during tokenization
     ///
     /// Lowercase conversion reduces vocabulary size and improves pattern recognition.
     #[arg(long, default_value_t = true)]
     pub lowercase: bool,
 
+    /// Disable restoring the original casing of lowercased tokens
+    ///
+    /// By default, proper nouns and words like "I" keep the casing they most
+    /// often had in the training text.
+    #[arg(long)]
+    pub no_truecase: bool,
+
     /// Whether to preserve punctuation as separate tokens
     ///
     /// Preserving punctuation helps maintain sentence structure and readability.
@@ -69,6 +189,26 @@ pub struct CliArgs {
     #[arg(long, default_value_t = true)]
     pub preserve_sentence_boundaries: bool,
 
+    /// Whether to preserve paragraph breaks and section headings during tokenization
+    ///
+    /// This lets the model learn where paragraphs end and headings appear.
+    #[arg(long, default_value_t = true)]
+    pub preserve_paragraphs: bool,
+
+    /// Reduce training words to their stem using the stemmer for this language
+    ///
+    /// Merges inflections such as "runs" and "running" to shrink the vocabulary.
+    /// Available languages: en, fr, es, de, ru, it, pt.
+    #[arg(long)]
+    pub stem: Option<String>,
+
+    /// Disable section headings in the generated text
+    ///
+    /// Headings learned from the training text are dropped from the output,
+    /// while paragraph breaks are kept.
+    #[arg(long)]
+    pub no_headings: bool,
+
     /// Minimum occurrences for pruning rare n-grams
     ///
     /// Higher values create smaller models but may reduce quality.
@@ -102,6 +242,37 @@ pub struct CliArgs {
     #[arg(long)]
     pub no_insights: bool,
 
+    /// Stopword lists to exclude from insights and the wordcloud (comma-separated)
+    ///
+    /// Available languages: en, fr, es, de, ru.
+    /// Example: --stopwords en,fr
+    #[arg(long, value_delimiter = ',')]
+    pub stopwords: Vec<String>,
+
+    /// Path to a file with additional stopwords, one per line
+    #[arg(long)]
+    pub stopwords_file: Option<PathBuf>,
+
+    /// Minimum word length counted by insights and the wordcloud
+    #[arg(long, default_value_t = 3)]
+    pub min_word_length: usize,
+
+    /// Maximum word length counted by insights and the wordcloud
+    #[arg(long)]
+    pub max_word_length: Option<usize>,
+
+    /// Regex of words to exclude from insights and the wordcloud
+    ///
+    /// Example: --exclude-words "^\d+$" to ignore numbers
+    #[arg(long)]
+    pub exclude_words: Option<String>,
+
+    /// Count word forms sharing a stem together in insights and the wordcloud
+    ///
+    /// Example: --insights-stem en counts "run", "runs" and "running" as one word
+    #[arg(long)]
+    pub insights_stem: Option<String>,
+
     /// Enable verbose logging
     ///
     /// This will print additional information during execution.
@@ -112,3 +283,35 @@ pub struct CliArgs {
 pub fn parse_args() -> CliArgs {
     CliArgs::parse()
 }
+
+/// Parses a number between 0.0 and 1.0 inclusive
+fn parse_fraction(value: &str) -> Result<f64, String> {
+    let fraction: f64 = value
+        .parse()
+        .map_err(|_| format!("`{}` is not a number", value))?;
+    if (0.0..=1.0).contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err(format!("{} is not between 0.0 and 1.0", fraction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_threshold_range() {
+        let parse = |threshold: &str| {
+            CliArgs::try_parse_from(["text-gen-ngram", "--dedup-threshold", threshold])
+                .map(|args| args.dedup_threshold)
+        };
+
+        assert_eq!(parse("0.5").unwrap(), 0.5);
+        assert_eq!(parse("1").unwrap(), 1.0);
+        assert!(parse("1.5").is_err());
+        assert!(parse("-0.1").is_err());
+        assert!(parse("NaN").is_err());
+        assert!(parse("high").is_err());
+    }
+}
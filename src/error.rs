@@ -23,6 +23,9 @@ pub enum TextGenError {
     #[error("Tokenization error: {0}")]
     Tokenization(String),
 
+    #[error("Input error: {0}")]
+    Input(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -0,0 +1,278 @@
+use crate::error::{Result, TextGenError};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// Followed by the block size, `1` to `9`
+const BZIP2_MAGIC: &[u8] = b"BZh";
+/// Bytes needed to tell every format apart
+const MAGIC_LEN: usize = 4;
+
+/// Compression formats understood by the input readers and output writers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the format from the first bytes of a stream
+    pub fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if magic.starts_with(ZSTD_MAGIC) {
+            Self::Zstd
+        } else if magic.starts_with(BZIP2_MAGIC)
+            && magic
+                .get(BZIP2_MAGIC.len())
+                .is_some_and(|size| (b'1'..=b'9').contains(size))
+        {
+            Self::Bzip2
+        } else {
+            Self::None
+        }
+    }
+
+    /// Parses a format name ("gzip", "zstd", "bzip2" or "none")
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "gz" | "gzip" => Ok(Self::Gzip),
+            "zst" | "zstd" => Ok(Self::Zstd),
+            "bz2" | "bzip2" => Ok(Self::Bzip2),
+            other => Err(TextGenError::Config(format!(
+                "Unknown compression format '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Guesses the format of a file to be written from its extension
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| Self::from_name(ext).ok())
+            .unwrap_or(Self::None)
+    }
+
+    /// File extension for this format, without the dot
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+            Self::Bzip2 => Some("bz2"),
+        }
+    }
+
+    /// Appends this format's extension to `path` ("data.txt" -> "data.txt.gz")
+    pub fn apply_extension(self, path: impl Into<PathBuf>) -> PathBuf {
+        let path = path.into();
+        match self.extension() {
+            Some(ext) => {
+                let mut name = path.into_os_string();
+                name.push(".");
+                name.push(ext);
+                name.into()
+            }
+            None => path,
+        }
+    }
+}
+
+/// Wraps `reader` in a decoder chosen from its magic bytes
+///
+/// Data is decompressed as it is read, so large archives never have to be
+/// held in memory in compressed and decompressed form at once.
+pub fn decompress<'a, R: BufRead + Send + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn BufRead + Send + 'a>> {
+    // Pipes may hand over fewer bytes per read than the magic number is long
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut reader)
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)
+        .map_err(TextGenError::Io)?;
+    let compression = Compression::detect(&magic);
+    let reader = io::Cursor::new(magic).chain(reader);
+
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(reader).map_err(TextGenError::Io)?,
+        )),
+        Compression::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+    })
+}
+
+/// Opens a possibly compressed file for buffered reading
+pub fn open(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path).map_err(|e| with_path(e, path))?;
+    decompress(BufReader::new(file))
+}
+
+/// Reads a possibly compressed file into a string
+pub fn read_to_string(path: &Path) -> Result<String> {
+    let mut text = String::new();
+    open(path)?
+        .read_to_string(&mut text)
+        .map_err(|e| with_path(e, path))?;
+    Ok(text)
+}
+
+/// A file being written through one of the supported encoders
+///
+/// Call [`finish`](Self::finish) once everything is written: it ends the
+/// compressed stream and reports errors that dropping the writer would lose.
+pub struct CompressedWriter {
+    encoder: Encoder,
+}
+
+enum Encoder {
+    None(BufWriter<File>),
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+    Bzip2(bzip2::write::BzEncoder<BufWriter<File>>),
+}
+
+impl CompressedWriter {
+    /// Writes the stream trailer and flushes everything to the file
+    pub fn finish(self) -> Result<()> {
+        let mut file = match self.encoder {
+            Encoder::None(file) => file,
+            Encoder::Gzip(encoder) => encoder.finish().map_err(TextGenError::Io)?,
+            Encoder::Zstd(encoder) => encoder.finish().map_err(TextGenError::Io)?,
+            Encoder::Bzip2(encoder) => encoder.finish().map_err(TextGenError::Io)?,
+        };
+        file.flush().map_err(TextGenError::Io)
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::None(file) => file.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+            Encoder::Bzip2(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::None(file) => file.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+            Encoder::Bzip2(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Creates `path` for writing, compressing with `compression`
+///
+/// The file is only complete once [`CompressedWriter::finish`] returns.
+pub fn create(path: &Path, compression: Compression) -> Result<CompressedWriter> {
+    let file = BufWriter::new(File::create(path).map_err(|e| with_path(e, path))?);
+
+    let encoder = match compression {
+        Compression::None => Encoder::None(file),
+        Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        )),
+        Compression::Zstd => {
+            Encoder::Zstd(zstd::stream::write::Encoder::new(file, 0).map_err(TextGenError::Io)?)
+        }
+        Compression::Bzip2 => Encoder::Bzip2(bzip2::write::BzEncoder::new(
+            file,
+            bzip2::Compression::default(),
+        )),
+    };
+    Ok(CompressedWriter { encoder })
+}
+
+/// Writes `contents` to `path`, compressed with `compression`
+pub fn write(path: &Path, contents: &[u8], compression: Compression) -> Result<()> {
+    let mut writer = create(path, compression)?;
+    writer.write_all(contents).map_err(TextGenError::Io)?;
+    writer.finish()
+}
+
+fn with_path(error: io::Error, path: &Path) -> TextGenError {
+    TextGenError::Io(io::Error::new(
+        error.kind(),
+        format!("{}: {}", path.display(), error),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        let dir = std::env::temp_dir().join(format!("text-gen-compression-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = "Compressed corpora are read transparently.\n".repeat(50);
+
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+        ] {
+            let path = compression.apply_extension(dir.join("corpus.txt"));
+            write(&path, text.as_bytes(), compression).unwrap();
+
+            let mut magic = [0u8; 4];
+            File::open(&path).unwrap().read_exact(&mut magic).unwrap();
+            assert_eq!(Compression::detect(&magic), compression);
+            assert_eq!(Compression::from_path(&path), compression);
+            assert_eq!(read_to_string(&path).unwrap(), text);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_decompress_short_reads() {
+        let text = "Piped input arrives a few bytes at a time.\n".repeat(20);
+        let mut compressed = Vec::new();
+        let mut encoder = zstd::stream::write::Encoder::new(&mut compressed, 0).unwrap();
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        for (input, expected) in [
+            (compressed.as_slice(), text.as_bytes()),
+            (text.as_bytes(), text.as_bytes()),
+            (b"ab".as_slice(), b"ab".as_slice()),
+        ] {
+            let mut output = Vec::new();
+            decompress(BufReader::with_capacity(1, input))
+                .unwrap()
+                .read_to_end(&mut output)
+                .unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_detect_requires_full_magic() {
+        assert_eq!(Compression::detect(b"BZh91AY&SY"), Compression::Bzip2);
+        assert_eq!(Compression::detect(b"BZhello world"), Compression::None);
+        assert_eq!(Compression::detect(b"BZh"), Compression::None);
+        assert_eq!(Compression::detect(b"plain text"), Compression::None);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Compression::from_name("GZIP").unwrap(), Compression::Gzip);
+        assert_eq!(Compression::from_name("zst").unwrap(), Compression::Zstd);
+        assert!(Compression::from_name("lzma").is_err());
+    }
+}
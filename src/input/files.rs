@@ -0,0 +1,195 @@
+use super::compression::Compression;
+use super::formats::{self, ReadOptions};
+use crate::error::{Result, TextGenError};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use log::debug;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+const MAX_CONCURRENT_READS: usize = 16;
+
+/// A piece of training text together with where it came from
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputDocument {
+    pub source: String,
+    pub title: Option<String>,
+    pub text: String,
+}
+
+impl InputDocument {
+    pub fn new(source: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            title: None,
+            text: text.into(),
+        }
+    }
+
+    /// Sets the title, adding it as a `# ` heading unless the text already starts with one
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        if let Some(title) = &title
+            && !self.text.starts_with("# ")
+        {
+            self.text = format!("# {}\n\n{}", title, self.text);
+        }
+        self.title = title;
+        self
+    }
+}
+
+/// Lists files under `dir` (recursively) or matching a glob `pattern`
+///
+/// Only files whose extension is in `extensions` are kept; an empty list keeps
+/// every file. Paths are sorted so training is reproducible. Symbolic links to
+/// files are read, but links to directories are not followed, so a link loop
+/// cannot recurse forever.
+pub fn collect_files(
+    dir: Option<&Path>,
+    pattern: Option<&str>,
+    extensions: &[String],
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    if let Some(dir) = dir {
+        walk_dir(dir, &mut files)?;
+    }
+
+    if let Some(pattern) = pattern {
+        let paths = glob::glob(pattern).map_err(|e| {
+            TextGenError::Config(format!("Invalid input glob '{}': {}", pattern, e))
+        })?;
+        for path in paths {
+            let path = path.map_err(|e| TextGenError::Io(e.into()))?;
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.retain(|path| has_extension(path, extensions));
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).map_err(TextGenError::Io)? {
+        let entry = entry.map_err(TextGenError::Io)?;
+        let path = entry.path();
+        // Unlike `Path::is_dir`, the entry's file type does not follow symlinks
+        let file_type = entry.file_type().map_err(TextGenError::Io)?;
+        if file_type.is_dir() {
+            walk_dir(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        } else if file_type.is_symlink() {
+            debug!("Not following symlink {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Checks the file extension, looking past a compression suffix ("a.txt.gz" is a "txt" file)
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+    let path = match Compression::from_path(path) {
+        Compression::None => path,
+        _ => Path::new(path.file_stem().unwrap_or_default()),
+    };
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|wanted| wanted.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Reads `paths` concurrently, returning their documents in the same order
+///
+/// Compressed files (gzip, zstd, bzip2) are decompressed transparently and
+/// structured formats are converted to text with [`formats::read_file`].
+pub async fn read_documents(
+    paths: &[PathBuf],
+    options: &ReadOptions,
+) -> Result<Vec<InputDocument>> {
+    let documents: Vec<Vec<InputDocument>> = stream::iter(paths)
+        .map(|path| async move {
+            debug!("Reading {}", path.display());
+            let (path, options) = (path.clone(), options.clone());
+            task::spawn_blocking(move || formats::read_file(&path, &options))
+                .await
+                .map_err(|e| TextGenError::Unknown(e.to_string()))?
+        })
+        .buffered(MAX_CONCURRENT_READS)
+        .try_collect()
+        .await?;
+
+    Ok(documents.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::compression;
+
+    fn sample_tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("text-gen-input-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("a.txt"), "first file").unwrap();
+        std::fs::write(root.join("nested/b.TXT"), "second file").unwrap();
+        std::fs::write(root.join("nested/c.json"), "{}").unwrap();
+        compression::write(
+            &root.join("nested/d.txt.gz"),
+            b"third file",
+            Compression::Gzip,
+        )
+        .unwrap();
+        root
+    }
+
+    #[tokio::test]
+    async fn test_collect_and_read_directory() {
+        let root = sample_tree();
+
+        let files = collect_files(Some(&root), None, &["txt".to_string()]).unwrap();
+        assert_eq!(
+            files,
+            [
+                root.join("a.txt"),
+                root.join("nested/b.TXT"),
+                root.join("nested/d.txt.gz")
+            ]
+        );
+
+        let documents = read_documents(&files, &ReadOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(documents[0].text, "first file");
+        assert_eq!(documents[1].text, "second file");
+        assert_eq!(documents[2].text, "third file");
+
+        let pattern = format!("{}/**/*.json", root.display());
+        let files = collect_files(None, Some(&pattern), &[]).unwrap();
+        assert_eq!(files, [root.join("nested/c.json")]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_directories_are_not_followed() {
+        let root = std::env::temp_dir().join(format!("text-gen-symlinks-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("nested/a.txt"), "text").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("nested/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("nested/a.txt"), root.join("b.txt")).unwrap();
+
+        let files = collect_files(Some(&root), None, &[]).unwrap();
+        assert_eq!(files, [root.join("b.txt"), root.join("nested/a.txt")]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use super::html::extract_html;
+use crate::error::{Result, TextGenError};
+use crate::input::InputDocument;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use zip::ZipArchive;
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// Reads the chapters of an EPUB book in reading (spine) order
+///
+/// Each chapter becomes a document titled by its first heading.
+pub fn read_epub(bytes: &[u8], source: &str) -> Result<Vec<InputDocument>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| epub_error(source, e))?;
+
+    let container = read_entry(&mut archive, CONTAINER_PATH, source)?;
+    let container = parse_xml(&container, CONTAINER_PATH, source)?;
+    let package_path = container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|rootfile| rootfile.attribute("full-path"))
+        .ok_or_else(|| epub_error(source, "no rootfile in container.xml"))?
+        .to_string();
+
+    let package = read_entry(&mut archive, &package_path, source)?;
+    let package = parse_xml(&package, &package_path, source)?;
+    let section = |name: &str| {
+        package
+            .root_element()
+            .children()
+            .find(|node| node.has_tag_name(name))
+    };
+
+    let manifest: HashMap<&str, (&str, &str)> = section("manifest")
+        .into_iter()
+        .flat_map(|manifest| manifest.children())
+        .filter(|node| node.has_tag_name("item"))
+        .filter_map(|item| {
+            Some((
+                item.attribute("id")?,
+                (
+                    item.attribute("href")?,
+                    item.attribute("media-type").unwrap_or_default(),
+                ),
+            ))
+        })
+        .collect();
+    let spine = section("spine")
+        .into_iter()
+        .flat_map(|spine| spine.children())
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|itemref| itemref.attribute("idref"));
+
+    let mut documents = Vec::new();
+    for idref in spine {
+        let Some(&(href, media_type)) = manifest.get(idref) else {
+            continue;
+        };
+        if !media_type.contains("html") {
+            continue;
+        }
+
+        let path = resolve_href(&package_path, href);
+        let chapter = read_entry(&mut archive, &path, source)?;
+        let extracted = extract_html(&chapter);
+        if extracted.text.trim().is_empty() {
+            continue;
+        }
+
+        let title = extracted
+            .text
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("# "))
+            .map(String::from)
+            .or(extracted.title);
+        documents.push(
+            InputDocument::new(format!("{}#{}", source, path), extracted.text).with_title(title),
+        );
+    }
+
+    if documents.is_empty() {
+        return Err(epub_error(source, "no readable chapters"));
+    }
+    Ok(documents)
+}
+
+/// Resolves a manifest href against the package document's directory
+///
+/// Hrefs are percent-encoded URLs, so they are decoded and may climb out of
+/// the package directory with `..`.
+fn resolve_href(package_path: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = urlencoding::decode(href)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or_else(|_| href.to_string());
+
+    let mut segments: Vec<&str> = match href.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => package_path.split('/').collect(),
+    };
+    // Drop the package file name, keeping its directory
+    segments.pop();
+
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn parse_xml<'a>(xml: &'a str, name: &str, source: &str) -> Result<roxmltree::Document<'a>> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    roxmltree::Document::parse_with_options(xml, options)
+        .map_err(|e| epub_error(source, format!("{}: {}", name, e)))
+}
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    source: &str,
+) -> Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| epub_error(source, format!("{}: {}", name, e)))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| epub_error(source, format!("{}: {}", name, e)))?;
+    Ok(content)
+}
+
+fn epub_error(source: &str, error: impl std::fmt::Display) -> TextGenError {
+    TextGenError::Input(format!("Invalid EPUB {}: {}", source, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn build_epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_reads_chapters_in_spine_order() {
+        let epub = build_epub(&[
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package><manifest>
+                    <item id="c1" href="one.xhtml" media-type="application/xhtml+xml"/>
+                    <item id="c2" href="two%20b.xhtml" media-type="application/xhtml+xml"/>
+                    <item id="css" href="style.css" media-type="text/css"/>
+                  </manifest><spine><itemref idref="c2"/><itemref idref="c1"/></spine></package>"#,
+            ),
+            (
+                "OEBPS/one.xhtml",
+                "<html><body><h1>Chapter Two</h1><p>The end.</p></body></html>",
+            ),
+            (
+                "OEBPS/two b.xhtml",
+                "<html><head><title>Book</title></head><body><h2>Chapter One</h2><p>It began.</p></body></html>",
+            ),
+        ]);
+
+        let documents = read_epub(&epub, "book.epub").unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].title.as_deref(), Some("Chapter One"));
+        assert_eq!(documents[0].text, "# Chapter One\n\nIt began.");
+        assert_eq!(documents[0].source, "book.epub#OEBPS/two b.xhtml");
+        assert_eq!(documents[1].text, "# Chapter Two\n\nThe end.");
+    }
+
+    #[test]
+    fn test_reads_namespaced_package_with_relative_hrefs() {
+        let epub = build_epub(&[
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0"?>
+                <container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+                  <rootfiles>
+                    <rootfile media-type="application/oebps-package+xml"
+                              full-path = 'book/package/content.opf'/>
+                  </rootfiles>
+                </container>"#,
+            ),
+            (
+                "book/package/content.opf",
+                r#"<?xml version="1.0"?>
+                <opf:package xmlns:opf="http://www.idpf.org/2007/opf" version="2.0">
+                  <opf:metadata><!-- <opf:item id="fake" href="fake.xhtml"/> --></opf:metadata>
+                  <opf:manifest>
+                    <opf:item href="../text/ch1.xhtml" media-type="application/xhtml+xml" id="ch1"/>
+                  </opf:manifest>
+                  <opf:spine><opf:itemref idref="ch1"/></opf:spine>
+                </opf:package>"#,
+            ),
+            (
+                "book/text/ch1.xhtml",
+                "<html><body><h1>Prologue</h1><p>Once.</p></body></html>",
+            ),
+        ]);
+
+        let documents = read_epub(&epub, "book.epub").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].title.as_deref(), Some("Prologue"));
+        assert_eq!(documents[0].source, "book.epub#book/text/ch1.xhtml");
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        assert_eq!(resolve_href("content.opf", "ch1.xhtml"), "ch1.xhtml");
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "./text/ch%201.xhtml#start"),
+            "OEBPS/text/ch 1.xhtml"
+        );
+        assert_eq!(
+            resolve_href("a/b/content.opf", "../../ch1.xhtml"),
+            "ch1.xhtml"
+        );
+        assert_eq!(resolve_href("a/content.opf", "/ch1.xhtml"), "ch1.xhtml");
+    }
+
+    #[test]
+    fn test_rejects_non_epub() {
+        assert!(matches!(
+            read_epub(b"plain text", "notes.epub"),
+            Err(TextGenError::Input(_))
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use super::{ExtractedText, TextBlocks};
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html, Node, Selector};
+
+static TITLE: Lazy<Selector> = Lazy::new(|| Selector::parse("title").unwrap());
+
+/// Elements whose content is never prose
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+    "button", "select", "iframe", "head",
+];
+
+const HEADINGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Elements that start a new paragraph
+const BLOCKS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "li",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "pre",
+    "table",
+    "tr",
+    "figure",
+    "figcaption",
+    "body",
+    "hr",
+];
+
+/// Extracts readable prose from an HTML page
+///
+/// Headings become `# ` lines and block elements become paragraphs; scripts,
+/// navigation, headers and footers are dropped. The title is the page's
+/// `<title>`, or its first heading.
+pub fn extract_html(html: &str) -> ExtractedText {
+    let document = Html::parse_document(html);
+    let mut blocks = TextBlocks::default();
+    let mut first_heading = None;
+
+    walk(document.root_element(), &mut blocks, &mut first_heading);
+
+    let title = document
+        .select(&TITLE)
+        .next()
+        .map(|title| title.text().collect::<Vec<_>>().join(" "))
+        .map(|title| title.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|title| !title.is_empty())
+        .or(first_heading);
+
+    ExtractedText {
+        title,
+        text: blocks.finish(),
+    }
+}
+
+fn walk(element: ElementRef, blocks: &mut TextBlocks, first_heading: &mut Option<String>) {
+    let name = element.value().name();
+    if SKIPPED.contains(&name) {
+        return;
+    }
+
+    let heading = HEADINGS.contains(&name);
+    let block = BLOCKS.contains(&name);
+    if heading {
+        blocks.start_heading();
+    } else if block {
+        blocks.end_block();
+    }
+
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => blocks.push_text(text),
+            Node::Element(child_element) if child_element.name() == "br" => {
+                blocks.push_line_break()
+            }
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    walk(child, blocks, first_heading);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if heading || block {
+        let text = blocks.end_block();
+        if first_heading.is_none() {
+            *first_heading = text;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_headings_and_paragraphs() {
+        let html = r#"<html><head><title>Rust  Notes</title><style>p { color: red }</style></head>
+            <body>
+              <nav><a href="/">Home</a></nav>
+              <h1>Ownership</h1>
+              <p>Every value has an <b>owner</b>.</p>
+              <p>Values are dropped<br>when the owner goes away.</p>
+              <script>track();</script>
+              <footer>All rights reserved</footer>
+            </body></html>"#;
+
+        let extracted = extract_html(html);
+        assert_eq!(extracted.title.as_deref(), Some("Rust Notes"));
+        assert_eq!(
+            extracted.text,
+            "# Ownership\n\nEvery value has an owner.\n\nValues are dropped\nwhen the owner goes away."
+        );
+    }
+
+    #[test]
+    fn test_title_falls_back_to_first_heading() {
+        let extracted = extract_html("<h2>Chapter One</h2><p>It began.</p>");
+        assert_eq!(extracted.title.as_deref(), Some("Chapter One"));
+    }
+}
@@ -0,0 +1,65 @@
+use super::{ExtractedText, TextBlocks};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+/// Extracts prose from Markdown
+///
+/// Code blocks, images and raw HTML are dropped, links keep only their text,
+/// and headings of every level become `# ` lines.
+pub fn extract_markdown(markdown: &str) -> ExtractedText {
+    let mut blocks = TextBlocks::default();
+    let mut title = None;
+    let mut skipping = 0usize;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Image { .. } | Tag::HtmlBlock) => skipping += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::Image | TagEnd::HtmlBlock) => {
+                skipping = skipping.saturating_sub(1)
+            }
+            _ if skipping > 0 => {}
+            Event::Start(Tag::Heading { .. }) => blocks.start_heading(),
+            Event::End(TagEnd::Heading(_)) => {
+                let heading = blocks.end_block();
+                if title.is_none() {
+                    title = heading;
+                }
+            }
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Item | TagEnd::BlockQuote(_) | TagEnd::TableRow,
+            ) => {
+                blocks.end_block();
+            }
+            Event::Text(text) | Event::Code(text) => blocks.push_text(&text),
+            Event::SoftBreak | Event::End(TagEnd::TableCell) => blocks.push_text(" "),
+            Event::HardBreak => blocks.push_line_break(),
+            _ => {}
+        }
+    }
+
+    ExtractedText {
+        title,
+        text: blocks.finish(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_code_and_link_syntax() {
+        let markdown = "## Getting started\n\n\
+                        Install it with [Cargo](https://doc.rust-lang.org/cargo/) and\n\
+                        run `cargo build`.\n\n\
+                        ```sh\ncargo run\n```\n\n\
+                        ![logo](logo.png)\n\n\
+                        - First item\n- Second item";
+
+        let extracted = extract_markdown(markdown);
+        assert_eq!(extracted.title.as_deref(), Some("Getting started"));
+        assert_eq!(
+            extracted.text,
+            "# Getting started\n\nInstall it with Cargo and run cargo build.\n\nFirst item\n\nSecond item"
+        );
+    }
+}
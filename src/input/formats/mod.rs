@@ -0,0 +1,269 @@
+pub mod epub;
+pub mod html;
+pub mod markdown;
+pub mod records;
+pub mod subtitles;
+
+pub use records::{FieldFilter, RecordSelector};
+
+use super::InputDocument;
+use super::compression::{self, Compression};
+use crate::error::{Result, TextGenError};
+use std::io::Read;
+use std::path::Path;
+
+/// File formats training text can be extracted from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Pick the format from the file extension
+    #[default]
+    Auto,
+    Text,
+    Html,
+    Markdown,
+    Epub,
+    /// SRT or WebVTT subtitles
+    Subtitles,
+    /// One document per row, taken from a named column
+    Csv,
+    /// One document per JSON record, taken from a key or JSON pointer
+    JsonLines,
+}
+
+impl InputFormat {
+    /// Parses a format name ("auto", "text", "html", "markdown", "epub", "subtitles",
+    /// "csv" or "jsonl")
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "text" | "txt" => Ok(Self::Text),
+            "html" | "htm" | "xhtml" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "epub" => Ok(Self::Epub),
+            "subtitles" | "srt" | "vtt" => Ok(Self::Subtitles),
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::JsonLines),
+            other => Err(TextGenError::Config(format!(
+                "Unknown input format '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Guesses the format from an extension, looking past compression suffixes
+    pub fn from_path(path: &Path) -> Self {
+        let path = match Compression::from_path(path) {
+            Compression::None => path,
+            _ => Path::new(path.file_stem().unwrap_or_default()),
+        };
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| Self::from_name(ext).ok())
+            .filter(|format| *format != Self::Auto)
+            .unwrap_or(Self::Text)
+    }
+
+    /// Resolves `Auto` for a given file
+    pub fn resolve(self, path: &Path) -> Self {
+        match self {
+            Self::Auto => Self::from_path(path),
+            format => format,
+        }
+    }
+}
+
+/// How input files are turned into documents
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOptions {
+    pub format: InputFormat,
+    /// Field and filter used by CSV and JSON Lines input
+    pub records: RecordSelector,
+}
+
+impl ReadOptions {
+    pub fn new(format: InputFormat) -> Self {
+        Self {
+            format,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_records(mut self, records: RecordSelector) -> Self {
+        self.records = records;
+        self
+    }
+}
+
+/// Reads a (possibly compressed) file and extracts its documents
+pub fn read_file(path: &Path, options: &ReadOptions) -> Result<Vec<InputDocument>> {
+    let mut bytes = Vec::new();
+    compression::open(path)?
+        .read_to_end(&mut bytes)
+        .map_err(TextGenError::Io)?;
+    let options = ReadOptions {
+        format: options.format.resolve(path),
+        ..options.clone()
+    };
+    parse(bytes, &path.display().to_string(), &options)
+}
+
+/// Extracts documents from raw file contents in the given format
+///
+/// Plain text, HTML, Markdown and subtitles yield one document; EPUB books yield one per
+/// chapter and CSV and JSON Lines one per record. Titles become `# ` heading lines so
+/// section markers survive.
+pub fn parse(bytes: Vec<u8>, source: &str, options: &ReadOptions) -> Result<Vec<InputDocument>> {
+    match options.format {
+        InputFormat::Epub => return epub::read_epub(&bytes, source),
+        InputFormat::Csv => return records::read_csv(&bytes, source, &options.records),
+        _ => {}
+    }
+
+    let text = String::from_utf8(bytes)
+        .map_err(|_| TextGenError::Input(format!("{} is not valid UTF-8", source)))?;
+
+    let document = match options.format {
+        InputFormat::Html => {
+            let extracted = html::extract_html(&text);
+            InputDocument::new(source, extracted.text).with_title(extracted.title)
+        }
+        InputFormat::Markdown => {
+            let extracted = markdown::extract_markdown(&text);
+            InputDocument::new(source, extracted.text).with_title(extracted.title)
+        }
+        InputFormat::Subtitles => InputDocument::new(source, subtitles::extract_subtitles(&text)),
+        InputFormat::JsonLines => {
+            return records::read_json_lines(&text, source, &options.records);
+        }
+        _ => InputDocument::new(source, text),
+    };
+
+    Ok(vec![document])
+}
+
+/// Prose extracted from a structured document
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtractedText {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Accumulates inline text into paragraphs and `# ` headings
+#[derive(Default)]
+pub(crate) struct TextBlocks {
+    blocks: Vec<String>,
+    current: String,
+    heading: bool,
+}
+
+impl TextBlocks {
+    pub(crate) fn push_text(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.current.push(' ');
+            }
+            self.current.push_str(line);
+        }
+    }
+
+    pub(crate) fn push_line_break(&mut self) {
+        if self.heading {
+            self.current.push(' ');
+        } else {
+            self.current.push('\n');
+        }
+    }
+
+    pub(crate) fn start_heading(&mut self) {
+        self.end_block();
+        self.heading = true;
+    }
+
+    /// Finishes the current paragraph or heading, returning the heading text
+    pub(crate) fn end_block(&mut self) -> Option<String> {
+        let lines: Vec<String> = self
+            .current
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect();
+        self.current.clear();
+
+        let heading = std::mem::take(&mut self.heading);
+        if lines.is_empty() {
+            return None;
+        }
+
+        let block = lines.join("\n");
+        if heading {
+            self.blocks.push(format!("# {}", block));
+            Some(block)
+        } else {
+            self.blocks.push(block);
+            None
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.end_block();
+        self.blocks.join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            InputFormat::from_path(Path::new("book.epub")),
+            InputFormat::Epub
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("page.html.gz")),
+            InputFormat::Html
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("README.md")),
+            InputFormat::Markdown
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("episode.vtt")),
+            InputFormat::Subtitles
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("export.jsonl.zst")),
+            InputFormat::JsonLines
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("notes")),
+            InputFormat::Text
+        );
+        assert!(InputFormat::from_name("pdf").is_err());
+    }
+
+    #[test]
+    fn test_parse_markdown_adds_no_duplicate_title() {
+        let documents = parse(
+            b"# Guide\n\nSome text.".to_vec(),
+            "guide.md",
+            &ReadOptions::new(InputFormat::Markdown),
+        )
+        .unwrap();
+
+        assert_eq!(documents[0].title.as_deref(), Some("Guide"));
+        assert_eq!(documents[0].text, "# Guide\n\nSome text.");
+    }
+
+    #[test]
+    fn test_rejects_invalid_utf8() {
+        let result = parse(
+            vec![b'a', 0xff, b'b'],
+            "latin1.txt",
+            &ReadOptions::new(InputFormat::Text),
+        );
+
+        assert!(matches!(result, Err(TextGenError::Input(_))));
+    }
+}
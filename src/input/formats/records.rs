@@ -0,0 +1,283 @@
+use super::InputDocument;
+use crate::error::{Result, TextGenError};
+use log::debug;
+use serde_json::Value;
+
+/// Field read from each CSV row or JSON Lines record when none is given
+pub const DEFAULT_TEXT_FIELD: &str = "text";
+
+/// Chooses which field of each record becomes a document, and which records to keep
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordSelector {
+    /// CSV column name, JSON key or JSON pointer ("/post/body")
+    pub field: String,
+    pub filter: Option<FieldFilter>,
+}
+
+impl Default for RecordSelector {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXT_FIELD)
+    }
+}
+
+impl RecordSelector {
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            filter: None,
+        }
+    }
+
+    /// Keeps only records matching `filter`
+    pub fn with_filter(mut self, filter: FieldFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+}
+
+/// Condition on another field of a record, written `field == value` or `field != value`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldFilter {
+    pub field: String,
+    pub value: String,
+    pub negated: bool,
+}
+
+impl FieldFilter {
+    /// Parses `author == "x"`; the value may be quoted with single or double quotes
+    pub fn parse(expression: &str) -> Result<Self> {
+        // The first operator splits, so the value may contain either one
+        let (index, operator, negated) = [("==", false), ("!=", true)]
+            .into_iter()
+            .filter_map(|(operator, negated)| Some((expression.find(operator)?, operator, negated)))
+            .min_by_key(|(index, ..)| *index)
+            .ok_or_else(|| {
+                TextGenError::Config(format!(
+                    "Invalid record filter '{}': expected 'field == value' or 'field != value'",
+                    expression
+                ))
+            })?;
+        let field = &expression[..index];
+        let value = &expression[index + operator.len()..];
+
+        let field = field.trim();
+        if field.is_empty() {
+            return Err(TextGenError::Config(format!(
+                "Invalid record filter '{}': missing field name",
+                expression
+            )));
+        }
+
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|rest| rest.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+
+        Ok(Self {
+            field: field.to_string(),
+            value: value.to_string(),
+            negated,
+        })
+    }
+
+    /// Checks a record's value for the field; a missing field never equals anything
+    pub fn matches(&self, value: Option<&str>) -> bool {
+        (value == Some(self.value.as_str())) != self.negated
+    }
+}
+
+/// Reads one document per CSV row from the column named by `selector`
+pub fn read_csv(
+    bytes: &[u8],
+    source: &str,
+    selector: &RecordSelector,
+) -> Result<Vec<InputDocument>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(bytes);
+    let headers = reader.headers().map_err(|e| csv_error(source, e))?.clone();
+
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| {
+                TextGenError::Config(format!("{} has no column named '{}'", source, name))
+            })
+    };
+    let text_column = column(&selector.field)?;
+    let filter = match &selector.filter {
+        Some(filter) => Some((filter, column(&filter.field)?)),
+        None => None,
+    };
+
+    let mut documents = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| csv_error(source, e))?;
+        if let Some((filter, index)) = filter
+            && !filter.matches(record.get(index))
+        {
+            continue;
+        }
+
+        match record.get(text_column).map(str::trim) {
+            Some(text) if !text.is_empty() => {
+                documents.push(InputDocument::new(format!("{}#{}", source, row + 1), text));
+            }
+            _ => debug!("{} row {}: empty '{}'", source, row + 1, selector.field),
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Reads one document per JSON Lines record from the field named by `selector`
+pub fn read_json_lines(
+    text: &str,
+    source: &str,
+    selector: &RecordSelector,
+) -> Result<Vec<InputDocument>> {
+    let mut documents = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Value = serde_json::from_str(line).map_err(|e| {
+            TextGenError::Input(format!(
+                "Invalid JSON in {} line {}: {}",
+                source, line_number, e
+            ))
+        })?;
+
+        if let Some(filter) = &selector.filter {
+            let value = lookup(&record, &filter.field).and_then(value_text);
+            if !filter.matches(value.as_deref()) {
+                continue;
+            }
+        }
+
+        match lookup(&record, &selector.field).and_then(value_text) {
+            Some(text) if !text.trim().is_empty() => {
+                documents.push(InputDocument::new(
+                    format!("{}#{}", source, line_number),
+                    text.trim(),
+                ));
+            }
+            _ => debug!(
+                "{} line {}: no text in '{}'",
+                source, line_number, selector.field
+            ),
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Finds a field by JSON pointer ("/a/b") or top-level key
+fn lookup<'a>(record: &'a Value, field: &str) -> Option<&'a Value> {
+    if field.starts_with('/') {
+        record.pointer(field)
+    } else {
+        record.get(field)
+    }
+}
+
+/// Text of a JSON value; arrays of strings are joined as paragraphs
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Array(items) => {
+            let parts: Vec<&str> = items.iter().filter_map(Value::as_str).collect();
+            (!parts.is_empty()).then(|| parts.join("\n\n"))
+        }
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+fn csv_error(source: &str, error: csv::Error) -> TextGenError {
+    TextGenError::Input(format!("Invalid CSV {}: {}", source, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter = FieldFilter::parse(r#"author == "Jane Austen""#).unwrap();
+        assert_eq!(filter.field, "author");
+        assert_eq!(filter.value, "Jane Austen");
+        assert!(!filter.negated);
+        assert!(filter.matches(Some("Jane Austen")));
+        assert!(!filter.matches(None));
+
+        let filter = FieldFilter::parse("lang != 'en'").unwrap();
+        assert!(filter.negated);
+        assert!(filter.matches(Some("fr")));
+        assert!(filter.matches(None));
+
+        // The first operator splits; the value may contain the other one
+        let filter = FieldFilter::parse("title == 'a != b'").unwrap();
+        assert_eq!(filter.field, "title");
+        assert_eq!(filter.value, "a != b");
+        assert!(!filter.negated);
+        let filter = FieldFilter::parse("title != a == b").unwrap();
+        assert_eq!(filter.value, "a == b");
+        assert!(filter.negated);
+
+        assert!(FieldFilter::parse("author").is_err());
+        assert!(FieldFilter::parse(" == x").is_err());
+    }
+
+    #[test]
+    fn test_csv_column_with_filter() {
+        let csv = "id,author,body\n\
+                   1,alice,\"First post, with a comma.\"\n\
+                   2,bob,Second post.\n\
+                   3,alice,\n\
+                   4,alice,Third post.\n";
+        let selector =
+            RecordSelector::new("body").with_filter(FieldFilter::parse("author == alice").unwrap());
+
+        let documents = read_csv(csv.as_bytes(), "posts.csv", &selector).unwrap();
+        let texts: Vec<&str> = documents.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(texts, ["First post, with a comma.", "Third post."]);
+        assert_eq!(documents[1].source, "posts.csv#4");
+
+        assert!(read_csv(csv.as_bytes(), "posts.csv", &RecordSelector::default()).is_err());
+        assert!(matches!(
+            read_csv(b"text\n\xff\n", "posts.csv", &RecordSelector::default()),
+            Err(TextGenError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_lines_pointer() {
+        let jsonl = r#"{"user": {"name": "alice"}, "post": {"body": "Hello there."}}
+
+{"user": {"name": "bob"}, "post": {"body": "Not this one."}}
+{"user": {"name": "alice"}, "post": {"body": ["One.", "Two."]}}
+{"user": {"name": "alice"}}
+"#;
+        let selector = RecordSelector::new("/post/body")
+            .with_filter(FieldFilter::parse("/user/name == alice").unwrap());
+
+        let documents = read_json_lines(jsonl, "log.jsonl", &selector).unwrap();
+        let texts: Vec<&str> = documents.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(texts, ["Hello there.", "One.\n\nTwo."]);
+        assert_eq!(documents[1].source, "log.jsonl#4");
+
+        assert!(matches!(
+            read_json_lines("{broken", "log.jsonl", &selector),
+            Err(TextGenError::Input(_))
+        ));
+    }
+}
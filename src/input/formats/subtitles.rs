@@ -0,0 +1,142 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static TIMING: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*[\d:.,]+\s*-->\s*[\d:.,]+").unwrap());
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>|\{[^}]*\}").unwrap());
+static SOUND: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[[^\]]*\]|\([^)]*\)|♪[^♪]*♪?").unwrap());
+/// An all-caps speaker label ("JOHN:", "DR. WHO:")
+static SPEAKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Z][A-Z0-9 .'’-]*[A-Z0-9.]:\s+").unwrap());
+/// A capitalized name, only taken as a label after a speaker dash ("- Mary:")
+static NAMED_SPEAKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Z][a-z]+(?: [A-Z][a-z]+)?:\s+").unwrap());
+
+/// WebVTT blocks that never contain dialogue
+const VTT_METADATA: &[&str] = &["WEBVTT", "NOTE", "STYLE", "REGION"];
+
+/// Extracts dialogue from SRT or WebVTT subtitles
+///
+/// Cue numbers, timestamps, styling tags, sound descriptions and speaker
+/// labels are removed. Cues are joined until a sentence ends, so each line of
+/// the result is one utterance.
+pub fn extract_subtitles(subtitles: &str) -> String {
+    let subtitles = subtitles
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n");
+    let mut utterances = Vec::new();
+    let mut current = String::new();
+
+    for block in subtitles.split("\n\n") {
+        let block = block.trim_matches('\n');
+        if VTT_METADATA
+            .iter()
+            .any(|keyword| block.starts_with(keyword))
+        {
+            continue;
+        }
+
+        // Cue text follows the timing line; anything before it is a cue number or id
+        let mut lines = block.lines().skip_while(|line| !TIMING.is_match(line));
+        if lines.next().is_none() {
+            continue;
+        }
+
+        for line in lines {
+            let line = TAG.replace_all(line, "");
+            let line = SOUND.replace_all(&line, "");
+            let line = line.trim();
+
+            // A leading dash marks a change of speaker inside one cue
+            let (new_speaker, line) = match line.strip_prefix('-') {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, line),
+            };
+            let line = SPEAKER.replace(line, "");
+            let line = if new_speaker {
+                NAMED_SPEAKER.replace(&line, "")
+            } else {
+                line
+            };
+            let line = line
+                .trim()
+                .trim_start_matches("...")
+                .trim_start_matches('…');
+            if line.is_empty() {
+                continue;
+            }
+
+            if new_speaker {
+                flush(&mut current, &mut utterances);
+            }
+            if !current.is_empty() {
+                // An ellipsis at a cue boundary only marks the sentence as continuing
+                let continued = current.trim_end_matches("...").trim_end_matches('…').len();
+                current.truncate(continued);
+                current.push(' ');
+            }
+            current.push_str(line.trim_start());
+
+            if ends_sentence(&current) {
+                flush(&mut current, &mut utterances);
+            }
+        }
+    }
+    flush(&mut current, &mut utterances);
+
+    utterances.join("\n")
+}
+
+fn flush(current: &mut String, utterances: &mut Vec<String>) {
+    let utterance = current.trim().trim_end_matches("...").trim_end_matches('…');
+    if !utterance.is_empty() {
+        utterances.push(utterance.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    current.clear();
+}
+
+/// True if the text ends a sentence; a trailing ellipsis means it continues
+fn ends_sentence(text: &str) -> bool {
+    let text = text.trim_end_matches(['"', '\'', '”', '’', ')']);
+    !text.ends_with("...") && !text.ends_with('…') && text.ends_with(['.', '!', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:03,000\r\n<i>I was thinking...</i>\r\n\r\n\
+                   2\r\n00:00:03,500 --> 00:00:05,000\r\n...that we could go\r\nto the lake.\r\n\r\n\
+                   3\r\n00:00:06,000 --> 00:00:08,000\r\n- JOHN: Really?\r\n- [laughs] Yes!\r\n";
+
+        assert_eq!(
+            extract_subtitles(srt),
+            "I was thinking that we could go to the lake.\nReally?\nYes!"
+        );
+    }
+
+    #[test]
+    fn test_keeps_colons_in_speech() {
+        let srt = "1\n00:00:01,000 --> 00:00:03,000\nRemember: we leave at dawn.\n\n\
+                   2\n00:00:03,000 --> 00:00:05,000\nDR. WATSON: Paris: the city of light.\n";
+
+        assert_eq!(
+            extract_subtitles(srt),
+            "Remember: we leave at dawn.\nParis: the city of light."
+        );
+    }
+
+    #[test]
+    fn test_webvtt() {
+        let vtt = "WEBVTT\n\nNOTE written by hand\n\n\
+                   intro\n00:01.000 --> 00:04.000 align:start\n<v Mary>Where are you going</v>\n\n\
+                   00:04.500 --> 00:06.000\n<c.yellow>home</c>, {\\an8}she said.\n\n\
+                   00:07.000 --> 00:09.000\n- Mary: ♪ la la ♪ Fine.";
+
+        assert_eq!(
+            extract_subtitles(vtt),
+            "Where are you going home, she said.\nFine."
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod compression;
+pub mod files;
+pub mod formats;
+
+pub use compression::Compression;
+pub use files::{InputDocument, collect_files, read_documents};
+pub use formats::{FieldFilter, InputFormat, ReadOptions, RecordSelector};
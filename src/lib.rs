@@ -2,6 +2,7 @@ pub mod app;
 pub mod cli;
 pub mod config;
 pub mod error;
+pub mod input;
 pub mod model;
 pub mod output;
 pub mod scrapers;
@@ -15,4 +16,6 @@ pub use model::{Generator, ModelStats, NGramModel, Trainer};
 pub use output::insights::show_text_insights;
 pub use output::wordcloud::generate_wordcloud;
 pub use scrapers::{Scraper, ScraperConfig};
-pub use text::{TokenizerOptions, normalize_text, tokenize};
+pub use text::{
+    Detokenizer, LanguageProfile, TokenizerOptions, detokenize, normalize_text, tokenize,
+};
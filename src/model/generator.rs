@@ -1,15 +1,33 @@
 use super::ngram::NGramModel;
+use crate::text::Detokenizer;
 use log::debug;
 use rand::seq::IteratorRandom;
 use rand::{rng, rngs::ThreadRng};
 
 pub struct Generator<'a> {
     model: &'a NGramModel,
+    detokenizer: Detokenizer,
+    truecase: bool,
 }
 
 impl<'a> Generator<'a> {
     pub fn new(model: &'a NGramModel) -> Self {
-        Self { model }
+        Self {
+            model,
+            detokenizer: Detokenizer::default(),
+            truecase: true,
+        }
+    }
+
+    pub fn with_detokenizer(mut self, detokenizer: Detokenizer) -> Self {
+        self.detokenizer = detokenizer;
+        self
+    }
+
+    /// Restores the casing recorded during training before formatting output
+    pub fn with_truecasing(mut self, truecase: bool) -> Self {
+        self.truecase = truecase;
+        self
     }
 
     pub fn generate(&self, seed: Option<Vec<String>>, length: usize) -> String {
@@ -79,103 +97,10 @@ impl<'a> Generator<'a> {
             }
         }
 
-        self.format_generated_text(&result)
-    }
-
-    fn format_generated_text(&self, tokens: &[String]) -> String {
-        let mut formatted = String::new();
-        let mut capitalize_next = true;
-        let mut prev_token = "";
-
-        for (i, token) in tokens.iter().enumerate() {
-            // Handle section headers (# symbol)
-            if token == "#" && i < tokens.len() - 1 {
-                if !formatted.is_empty() && !formatted.ends_with('\n') {
-                    formatted.push_str("\n\n");
-                }
-                capitalize_next = true;
-                continue;
-            }
-
-            if token == "<SENTENCE>" {
-                // Replace sentence boundary marker with period
-                if !formatted.ends_with('.')
-                    && !formatted.ends_with('!')
-                    && !formatted.ends_with('?')
-                {
-                    formatted.push('.');
-                }
-                formatted.push(' ');
-                capitalize_next = true;
-                continue;
-            }
-
-            // Check if token is punctuation
-            let is_punct = token.len() == 1 && ",.!?;:()[]{}\"'".contains(token);
-
-            // Special handling for apostrophes in contractions
-            if token == "'" && i > 0 && i < tokens.len() - 1 {
-                let next_token = &tokens[i + 1];
-                // Check if this is likely a contraction (e.g., don't, can't, etc.)
-                if ["t", "s", "ll", "ve", "re", "d", "m"].contains(&next_token.as_str()) {
-                    formatted.push('\'');
-                    prev_token = token;
-                    continue;
-                }
-            }
-
-            if is_punct {
-                // No space before punctuation
-                formatted.push_str(token);
-                // Add space after punctuation unless it's opening bracket or quote
-                if !["(", "[", "{", "\"", "'"].contains(&token.as_str()) {
-                    formatted.push(' ');
-                }
-            } else {
-                // For normal words
-                // Don't add space after an apostrophe for contractions
-                if i > 0
-                    && !formatted.ends_with(' ')
-                    && !formatted.is_empty()
-                    && prev_token != "'"
-                    && !formatted.ends_with('\n')
-                {
-                    formatted.push(' ');
-                }
-
-                // Capitalize first word or after sentence boundary
-                if capitalize_next {
-                    if let Some(c) = token.chars().next() {
-                        let capitalized =
-                            c.to_uppercase().collect::<String>() + &token[c.len_utf8()..];
-                        formatted.push_str(&capitalized);
-                    } else {
-                        formatted.push_str(token);
-                    }
-                    capitalize_next = false;
-                } else {
-                    formatted.push_str(token);
-                }
-            }
-
-            prev_token = token;
-        }
-
-        // Ensure the text ends with proper punctuation
-        if !formatted.ends_with('.') && !formatted.ends_with('!') && !formatted.ends_with('?') {
-            formatted.push('.');
+        if self.truecase && !self.model.casing.is_empty() {
+            result = self.model.casing.apply(&result);
         }
 
-        // Clean up any remaining <SENTENCE> tags
-        let cleaned = formatted.replace("<SENTENCE>", ". ");
-
-        // Fix spacing issues
-        let cleaned = cleaned
-            .replace(" .", ".")
-            .replace(" ,", ",")
-            .replace(" !", "!")
-            .replace(" ?", "?");
-
-        cleaned.trim().to_string()
+        self.detokenizer.detokenize(&result)
     }
 }
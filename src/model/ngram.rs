@@ -1,12 +1,19 @@
+use crate::error::Result;
+use crate::input::{Compression, compression};
+use crate::text::TrueCaser;
 use rand::rng;
 use rand::seq::IndexedRandom;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Serialize, Deserialize)]
 pub struct NGramModel {
     pub n: usize,
+    #[serde(with = "context_map")]
     pub model: HashMap<Vec<String>, Vec<String>>,
+    #[serde(default)]
+    pub casing: TrueCaser,
     #[serde(skip)]
     pub stats: ModelStats,
 }
@@ -24,17 +31,23 @@ impl NGramModel {
         Self {
             n,
             model: HashMap::new(),
+            casing: TrueCaser::new(),
             stats: ModelStats::default(),
         }
     }
 
     pub fn add_tokens(&mut self, tokens: &[String]) {
+        self.insert_tokens(tokens);
+        self.update_stats();
+    }
+
+    /// Adds n-grams from `tokens` without recomputing the statistics
+    pub(crate) fn insert_tokens(&mut self, tokens: &[String]) {
         for window in tokens.windows(self.n + 1) {
             let key = window[..self.n].to_vec();
             let value = window[self.n].clone();
-            self.model.entry(key).or_insert_with(Vec::new).push(value);
+            self.model.entry(key).or_default().push(value);
         }
-        self.update_stats();
     }
 
     pub fn predict_next<R: rand::Rng>(&self, key: &[String], rng: &mut R) -> Option<String> {
@@ -44,9 +57,10 @@ impl NGramModel {
     }
 
     pub fn update_stats(&mut self) {
-        let mut stats = ModelStats::default();
-
-        stats.unique_contexts = self.model.len();
+        let mut stats = ModelStats {
+            unique_contexts: self.model.len(),
+            ..Default::default()
+        };
 
         let mut total_tokens = 0;
         let mut unique_continuations = 0;
@@ -157,6 +171,40 @@ impl NGramModel {
         let mut rng = rng();
         self.predict_next(context, &mut rng)
     }
+
+    /// Saves the model as JSON, compressed according to the file extension
+    /// (".gz", ".zst" or ".bz2")
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let mut writer = compression::create(path, Compression::from_path(path))?;
+        serde_json::to_writer(&mut writer, self)?;
+        writer.finish()
+    }
+
+    /// Loads a model written by [`NGramModel::save`], compressed or not
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let reader = compression::open(path.as_ref())?;
+        let mut model: Self = serde_json::from_reader(reader)?;
+        model.update_stats();
+        Ok(model)
+    }
+}
+
+/// Stores the context map as a list of pairs, since JSON keys must be strings
+mod context_map {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    type ContextMap = HashMap<Vec<String>, Vec<String>>;
+
+    pub fn serialize<S: Serializer>(map: &ContextMap, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ContextMap, D::Error> {
+        let pairs: Vec<(Vec<String>, Vec<String>)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
 }
 
 #[cfg(test)]
@@ -211,4 +259,40 @@ mod tests {
             Some("b".to_string())
         );
     }
+
+    #[test]
+    fn test_save_and_load_compressed() {
+        use std::io::Read;
+
+        let mut model = NGramModel::new(1);
+        model.add_tokens(&["a".to_string(), "b".to_string(), "a".to_string()]);
+
+        for extension in ["json", "json.gz", "json.zst", "json.bz2"] {
+            let path = std::env::temp_dir().join(format!(
+                "text-gen-model-{}.{}",
+                std::process::id(),
+                extension
+            ));
+            model.save(&path).unwrap();
+
+            // A strict decoder fails on a stream without its trailer
+            let file = std::fs::File::open(&path).unwrap();
+            let mut json = String::new();
+            match Compression::from_path(&path) {
+                Compression::None => Box::new(file) as Box<dyn Read>,
+                Compression::Gzip => Box::new(flate2::read::GzDecoder::new(file)),
+                Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file).unwrap()),
+                Compression::Bzip2 => Box::new(bzip2::read::BzDecoder::new(file)),
+            }
+            .read_to_string(&mut json)
+            .unwrap();
+            assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+
+            let loaded = NGramModel::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.model, model.model);
+            assert_eq!(loaded.get_stats().total_tokens, 2);
+        }
+    }
 }
@@ -1,8 +1,15 @@
-use crate::error::Result;
+use crate::error::{Result, TextGenError};
 use crate::model::NGramModel;
 use crate::scrapers::scraper_trait::Scraper;
-use crate::text::TokenizerOptions;
-use crate::text::processing::{normalize_text, tokenize_large_text};
+use crate::text::processing::{is_heading, normalize_text, tokenize, tokenize_before};
+use crate::text::{Stemmer, TokenizerOptions};
+use std::io::BufRead;
+
+/// Approximate amount of text tokenized at once by [`Trainer::add_reader`]
+const STREAM_BATCH_BYTES: usize = 1_000_000;
+
+/// Size at which a batch is cut at a line break when no paragraph break came along
+const STREAM_BATCH_MAX_BYTES: usize = 4 * STREAM_BATCH_BYTES;
 
 pub struct Trainer {
     n: usize,
@@ -23,17 +30,226 @@ impl Trainer {
     }
 
     pub async fn train(&self, scraper: &dyn Scraper) -> Result<NGramModel> {
-        let text = scraper.fetch_text().await?;
-        self.train_from_text(&text)
+        let documents = scraper.fetch_documents().await?;
+        let texts: Vec<String> = documents
+            .iter()
+            .map(|document| document.to_text())
+            .collect();
+        self.train_from_documents(&texts)
     }
 
     pub fn train_from_text(&self, text: &str) -> Result<NGramModel> {
-        let normalized_text = normalize_text(text);
-
-        let tokens = tokenize_large_text(&normalized_text, &self.tokenizer_options)?;
+        self.train_from_documents(&[text])
+    }
 
+    /// Trains on several documents without letting n-grams span their boundaries
+    pub fn train_from_documents<S: AsRef<str>>(&self, documents: &[S]) -> Result<NGramModel> {
         let mut model = NGramModel::new(self.n);
-        model.add_tokens(&tokens);
+        for document in documents {
+            self.add_document(&mut model, document.as_ref())?;
+        }
+        model.update_stats();
         Ok(model)
     }
+
+    /// Tokenizes `text` into `model` as a separate document and returns its token count
+    ///
+    /// Statistics are not refreshed; call [`NGramModel::update_stats`] once
+    /// every document has been added.
+    pub fn add_document(&self, model: &mut NGramModel, text: &str) -> Result<usize> {
+        let tokens = self.prepare_tokens(model, text, None)?;
+        model.insert_tokens(&tokens);
+        Ok(tokens.len())
+    }
+
+    /// Streams text from `reader` into `model` as one document
+    ///
+    /// Text is tokenized in batches that end on paragraph breaks, carrying the
+    /// last n tokens over so n-grams still span batches. A paragraph that runs
+    /// past [`STREAM_BATCH_MAX_BYTES`] is cut at a line break instead. The
+    /// result matches [`Trainer::add_document`] without holding the whole text
+    /// in memory.
+    pub fn add_reader<R: BufRead>(&self, model: &mut NGramModel, reader: R) -> Result<usize> {
+        self.add_reader_batched(model, reader, STREAM_BATCH_BYTES, STREAM_BATCH_MAX_BYTES)
+    }
+
+    fn add_reader_batched<R: BufRead>(
+        &self,
+        model: &mut NGramModel,
+        reader: R,
+        batch_bytes: usize,
+        max_batch_bytes: usize,
+    ) -> Result<usize> {
+        let mut batch = String::new();
+        let mut pending: Option<String> = None;
+        let mut context: Vec<String> = Vec::new();
+        let mut total = 0;
+
+        for line in reader.lines() {
+            let line = line.map_err(TextGenError::Io)?;
+            let paragraph_end = line.trim().is_empty();
+            batch.push_str(&line);
+            batch.push('\n');
+
+            let full = paragraph_end && batch.len() >= batch_bytes;
+            if (full || batch.len() >= max_batch_bytes) && !batch.trim().is_empty() {
+                // A batch is only tokenized once the start of the next one is known
+                let next = std::mem::take(&mut batch);
+                if let Some(previous) = pending.replace(next) {
+                    total += self.add_batch(model, &previous, pending.as_deref(), &mut context)?;
+                }
+            }
+        }
+
+        if !batch.trim().is_empty()
+            && let Some(previous) = pending.replace(batch)
+        {
+            total += self.add_batch(model, &previous, pending.as_deref(), &mut context)?;
+        }
+        if let Some(last) = pending {
+            total += self.add_batch(model, &last, None, &mut context)?;
+        }
+
+        Ok(total)
+    }
+
+    fn add_batch(
+        &self,
+        model: &mut NGramModel,
+        batch: &str,
+        next: Option<&str>,
+        context: &mut Vec<String>,
+    ) -> Result<usize> {
+        let mut sequence = std::mem::take(context);
+        let context_len = sequence.len();
+        sequence.extend(self.prepare_tokens(model, batch, next)?);
+        model.insert_tokens(&sequence);
+
+        let added = sequence.len() - context_len;
+        *context = sequence.split_off(sequence.len().saturating_sub(self.n));
+        Ok(added)
+    }
+
+    /// Tokenizes `text` and feeds the truecaser
+    ///
+    /// When `text` is followed by another batch (`next`), the sentence and
+    /// paragraph markers between the two are emitted as for the whole text.
+    fn prepare_tokens(
+        &self,
+        model: &mut NGramModel,
+        text: &str,
+        next: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let normalized_text = normalize_text(text);
+
+        // Tokenize with the original casing so the truecaser can learn from it
+        let options = TokenizerOptions {
+            lowercase: false,
+            stemming: None,
+            ..self.tokenizer_options.clone()
+        };
+        let mut tokens = match next {
+            Some(next) => tokenize_before(
+                &normalized_text,
+                next,
+                breaks_paragraph(text, next),
+                &options,
+            ),
+            None => tokenize(&normalized_text, &options),
+        };
+
+        if self.tokenizer_options.lowercase {
+            model.casing.observe(&tokens);
+            for token in tokens.iter_mut() {
+                *token = token.to_lowercase();
+            }
+        }
+        if let Some(language) = self.tokenizer_options.stemming {
+            Stemmer::new(language).stem_tokens(&mut tokens);
+        }
+        Ok(tokens)
+    }
+}
+
+/// Whether [`normalize_text`] puts a paragraph break where `text` and `next` meet
+///
+/// Besides blank lines, a heading on either side of the cut starts or ends a paragraph.
+fn breaks_paragraph(text: &str, next: &str) -> bool {
+    let last_line = text.strip_suffix('\n').unwrap_or(text).rsplit('\n').next();
+    let first_line = next.split('\n').next();
+    [last_line, first_line].into_iter().flatten().any(|line| {
+        let line = line.trim();
+        line.is_empty() || is_heading(line)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_streaming_matches(trainer: &Trainer, text: &str, batch_bytes: usize, max: usize) {
+        let mut whole = NGramModel::new(trainer.n);
+        let whole_count = trainer.add_document(&mut whole, text).unwrap();
+
+        let mut streamed = NGramModel::new(trainer.n);
+        let streamed_count = trainer
+            .add_reader_batched(&mut streamed, text.as_bytes(), batch_bytes, max)
+            .unwrap();
+
+        assert_eq!(streamed_count, whole_count);
+        assert_eq!(streamed.model, whole.model);
+    }
+
+    #[test]
+    fn test_streaming_matches_whole_document() {
+        let text = "First paragraph here. It has two sentences.\n\n\
+                    Second one follows!\n\n\
+                    # Heading\n\n\
+                    and a lowercase paragraph\n\n\
+                    Last words.";
+
+        assert_streaming_matches(&Trainer::new(2), text, 1, usize::MAX);
+    }
+
+    #[test]
+    fn test_streaming_cuts_long_paragraphs_at_lines() {
+        let text = "A paragraph without blank lines.\n\
+                    It ends a sentence here.\n\
+                    continues in lower case\n\
+                    # Heading in the middle\n\
+                    Then Ölmeyen Ümit spans lines?\n\
+                    Yes.\n\n\
+                    Done.";
+        let without_punctuation = TokenizerOptions {
+            preserve_punctuation: false,
+            ..Default::default()
+        };
+
+        for trainer in [
+            Trainer::new(3),
+            Trainer::new(3).with_tokenizer_options(without_punctuation),
+        ] {
+            assert_streaming_matches(&trainer, text, 1, 1);
+            assert_streaming_matches(&trainer, text, usize::MAX, 40);
+        }
+    }
+
+    #[test]
+    fn test_add_reader_streams_large_non_ascii_text() {
+        let paragraph = "Jäät lähtivät järvestä. Ääni kuului kauas!\n\
+                         Лёд тронулся, господа присяжные заседатели.\n\n";
+        let text = paragraph.repeat(1_200_000 / paragraph.len());
+        let trainer = Trainer::new(2);
+
+        let mut whole = NGramModel::new(2);
+        let whole_count = trainer.add_document(&mut whole, &text).unwrap();
+
+        let mut streamed = NGramModel::new(2);
+        let streamed_count = trainer.add_reader(&mut streamed, text.as_bytes()).unwrap();
+
+        assert_eq!(streamed_count, whole_count);
+        assert_eq!(streamed.model, whole.model);
+        let key = ["лёд".to_string(), "тронулся".to_string()];
+        assert!(streamed.model[key.as_slice()].contains(&",".to_string()));
+    }
 }
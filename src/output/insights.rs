@@ -1,24 +1,29 @@
+use crate::text::vocabulary::{VocabularyFilter, words};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
 lazy_static! {
-    static ref RE_TOKEN: Regex = Regex::new(r"\w+").unwrap();
     static ref RE_SENTENCE: Regex = Regex::new(r"[.!?]+").unwrap();
 }
 
 pub fn show_text_insights(text: &str) {
+    show_text_insights_with(text, &VocabularyFilter::default());
+}
+
+/// Prints text insights, using `vocabulary` to pick the "most common words"
+pub fn show_text_insights_with(text: &str, vocabulary: &VocabularyFilter) {
     println!("\n📊 Text Insights 📊");
     println!("-----------------");
 
     let char_count = text.chars().count();
     println!("• Total characters: {}", char_count);
 
-    let words: Vec<_> = RE_TOKEN.find_iter(text).collect();
+    let words: Vec<&str> = words(text).collect();
     let word_count = words.len();
     println!("• Total words: {}", word_count);
 
-    let unique_words: HashSet<_> = words.iter().map(|m| m.as_str().to_lowercase()).collect();
+    let unique_words: HashSet<_> = words.iter().map(|w| w.to_lowercase()).collect();
     println!(
         "• Unique words: {} ({:.1}% of total)",
         unique_words.len(),
@@ -39,20 +44,14 @@ pub fn show_text_insights(text: &str) {
 
     let mut word_counts: HashMap<String, usize> = HashMap::new();
     for word in words.iter() {
-        let word_lower = word.as_str().to_lowercase();
+        let word_lower = word.to_lowercase();
         *word_counts.entry(word_lower).or_insert(0) += 1;
     }
 
-    let mut word_freq: Vec<(String, usize)> = word_counts
-        .iter()
-        .filter(|(word, _)| word.len() > 2)
-        .map(|(word, count)| (word.clone(), *count))
-        .collect();
-
-    word_freq.sort_by(|a, b| b.1.cmp(&a.1));
+    let word_freq = vocabulary.most_common(text, 5);
 
     println!("• Most common words:");
-    for (i, (word, count)) in word_freq.iter().take(5).enumerate() {
+    for (i, (word, count)) in word_freq.iter().enumerate() {
         println!(
             "  {}. \"{}\" (appears {} times, {:.1}%)",
             i + 1,
@@ -65,7 +64,7 @@ pub fn show_text_insights(text: &str) {
     let ttr = unique_words.len() as f64 / word_count as f64;
     println!("• Vocabulary richness (TTR): {:.3}", ttr);
 
-    let total_word_length: usize = words.iter().map(|m| m.as_str().len()).sum();
+    let total_word_length: usize = words.iter().map(|w| w.len()).sum();
     let avg_word_length = total_word_length as f64 / word_count as f64;
     println!("• Average word length: {:.1} characters", avg_word_length);
 
@@ -2,5 +2,5 @@ pub mod insights;
 pub mod wordcloud;
 
 // Re-export main functions for convenience
-pub use insights::show_text_insights;
-pub use wordcloud::generate_wordcloud;
+pub use insights::{show_text_insights, show_text_insights_with};
+pub use wordcloud::{generate_wordcloud, generate_wordcloud_with};
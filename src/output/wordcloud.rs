@@ -1,15 +1,21 @@
 use crate::error::{Result as TextGenResult, TextGenError};
+use crate::text::vocabulary::VocabularyFilter;
 use chrono::Utc;
 use log;
-use regex::Regex;
-use std::collections::HashMap;
 use wordcloud_rs::*;
 
+const MAX_WORDCLOUD_WORDS: usize = 100;
+
 pub fn generate_wordcloud(text: &str) -> TextGenResult<String> {
+    generate_wordcloud_with(text, &VocabularyFilter::default())
+}
+
+/// Generates a wordcloud from the words accepted by `vocabulary`
+pub fn generate_wordcloud_with(text: &str, vocabulary: &VocabularyFilter) -> TextGenResult<String> {
     let original_level = log::max_level();
     log::set_max_level(log::LevelFilter::Error);
 
-    let tokens = prepare_tokens_for_wordcloud(text);
+    let tokens = prepare_tokens_for_wordcloud(text, vocabulary);
 
     let wc = WordCloud::new().generate(tokens);
 
@@ -25,28 +31,10 @@ pub fn generate_wordcloud(text: &str) -> TextGenResult<String> {
     result.map(|_| output_path)
 }
 
-fn prepare_tokens_for_wordcloud(text: &str) -> Vec<(Token, f32)> {
-    let re = Regex::new(r"\w+").unwrap();
-    let tokens: Vec<String> = re
-        .find_iter(text)
-        .map(|m| m.as_str().to_lowercase())
-        .collect();
-
-    let mut counts: HashMap<String, usize> = HashMap::new();
-    for word in tokens {
-        if word.len() > 2 {
-            *counts.entry(word).or_default() += 1;
-        }
-    }
-
-    let mut wordcloud_tokens: Vec<(Token, f32)> = counts
+fn prepare_tokens_for_wordcloud(text: &str, vocabulary: &VocabularyFilter) -> Vec<(Token, f32)> {
+    vocabulary
+        .most_common(text, MAX_WORDCLOUD_WORDS)
         .into_iter()
-        .map(|(k, v)| (Token::Text(k), v as f32))
-        .collect();
-
-    wordcloud_tokens.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-    wordcloud_tokens.truncate(100);
-
-    wordcloud_tokens
+        .map(|(word, count)| (Token::Text(word), count as f32))
+        .collect()
 }
@@ -0,0 +1,351 @@
+use crate::error::{Result, TextGenError};
+use log::{debug, warn};
+use once_cell::sync::OnceCell;
+use reqwest::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, HeaderMap, HeaderName, LAST_MODIFIED,
+};
+use reqwest::{Request, RequestBuilder, Response, ResponseBuilderExt, Url};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default cache location, relative to the working directory
+pub const DEFAULT_CACHE_DIR: &str = ".http_cache";
+
+const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Request headers that change the response and so are part of the cache key
+const KEY_HEADERS: &[HeaderName] = &[AUTHORIZATION, ACCEPT];
+
+/// Response headers kept with a cached body
+const STORED_HEADERS: &[HeaderName] = &[CONTENT_TYPE, ETAG, LAST_MODIFIED];
+
+static SHARED_CACHE: OnceCell<Arc<ResponseCache>> = OnceCell::new();
+
+/// Installs the cache used by every scraper created afterwards
+///
+/// The first call wins; later calls are ignored.
+pub fn install(cache: ResponseCache) {
+    if SHARED_CACHE.set(Arc::new(cache)).is_err() {
+        debug!("HTTP cache already installed");
+    }
+}
+
+/// The cache installed with [`install`], if any
+pub fn shared() -> Option<Arc<ResponseCache>> {
+    SHARED_CACHE.get().cloned()
+}
+
+/// On-disk cache of successful GET responses, keyed by URL and relevant headers
+///
+/// Entries younger than the TTL are served directly; older ones are
+/// revalidated with `If-None-Match` / `If-Modified-Since`. In offline mode
+/// every request is answered from the cache regardless of age.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    offline: bool,
+}
+
+/// A stored response
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub url: String,
+    /// Where the request ended up after redirects, when that differs from `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Seconds since the Unix epoch when the entry was stored or last revalidated
+    pub stored_at: u64,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+/// What is written to an entry's metadata file
+///
+/// The body is stored in a separate file, so its hash is kept here to tell
+/// whether the two files on disk belong to the same response.
+#[derive(Serialize, Deserialize)]
+struct StoredEntry<T> {
+    #[serde(flatten)]
+    response: T,
+    body_hash: String,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            offline: false,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Serves everything from the cache and never touches the network
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Stable cache key for a request (FNV-1a of the URL and key headers)
+    pub fn key(request: &Request) -> String {
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bytes: &[u8]| hash = fnv1a(fnv1a(hash, bytes), b"\n");
+
+        feed(request.method().as_str().as_bytes());
+        feed(request.url().as_str().as_bytes());
+        for name in KEY_HEADERS {
+            for value in request.headers().get_all(name) {
+                feed(name.as_str().as_bytes());
+                feed(value.as_bytes());
+            }
+        }
+
+        format!("{:016x}", hash)
+    }
+
+    /// Looks up an entry, ignoring unreadable or mismatched files
+    pub fn get(&self, key: &str, url: &str) -> Option<CachedResponse> {
+        let meta = std::fs::read(self.meta_path(key)).ok()?;
+        let stored: StoredEntry<CachedResponse> = serde_json::from_slice(&meta).ok()?;
+        let mut entry = stored.response;
+        if entry.url != url {
+            debug!("Cache key collision for {}", url);
+            return None;
+        }
+        entry.body = std::fs::read(self.body_path(key)).ok()?;
+        if body_hash(&entry.body) != stored.body_hash {
+            debug!("Cached body of {} does not match its metadata", url);
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Writes a response body and its headers under `key`
+    ///
+    /// Both files are replaced atomically, and the headers record a hash of
+    /// the body, so a reader never pairs the body of one write with the
+    /// headers of another, nor sees a half-written entry.
+    pub fn put(&self, key: &str, entry: &CachedResponse) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).map_err(TextGenError::Io)?;
+        let stored = StoredEntry {
+            response: entry,
+            body_hash: body_hash(&entry.body),
+        };
+        write_atomic(&self.body_path(key), &entry.body)?;
+        write_atomic(&self.meta_path(key), &serde_json::to_vec_pretty(&stored)?)
+    }
+
+    /// Reads the response to a request for `url` into the cache and returns an equivalent response
+    pub async fn store(&self, key: &str, url: &str, response: Response) -> Result<Response> {
+        let final_url = response.url().as_str();
+        let entry = CachedResponse {
+            url: url.to_string(),
+            final_url: (final_url != url).then(|| final_url.to_string()),
+            status: response.status().as_u16(),
+            headers: stored_headers(response.headers()),
+            stored_at: now_secs(),
+            body: response.bytes().await.map_err(TextGenError::Http)?.to_vec(),
+        };
+
+        if let Err(e) = self.put(key, &entry) {
+            warn!("Failed to cache {}: {}", entry.url, e);
+        }
+        entry.to_response()
+    }
+
+    /// Marks an entry as fresh again after a `304 Not Modified`
+    pub fn touch(&self, key: &str, entry: &CachedResponse) {
+        let entry = CachedResponse {
+            stored_at: now_secs(),
+            ..entry.clone()
+        };
+        if let Err(e) = self.put(key, &entry) {
+            warn!("Failed to refresh cached {}: {}", entry.url, e);
+        }
+    }
+
+    fn meta_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn body_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.body", key))
+    }
+}
+
+impl CachedResponse {
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        now_secs().saturating_sub(self.stored_at) < ttl.as_secs()
+    }
+
+    fn header(&self, name: &HeaderName) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name.as_str()))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Adds `If-None-Match` / `If-Modified-Since` so the server can answer 304
+    pub fn add_validators(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = self.header(&ETAG) {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = self.header(&LAST_MODIFIED) {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, modified);
+        }
+        request
+    }
+
+    /// Rebuilds a response that callers can read like a live one, including its final URL
+    pub fn to_response(&self) -> Result<Response> {
+        let mut builder = http::Response::builder().status(self.status);
+        if let Ok(url) = Url::parse(self.final_url.as_deref().unwrap_or(&self.url)) {
+            builder = builder.url(url);
+        }
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(self.body.clone())
+            .map_err(|e| TextGenError::Unknown(format!("Invalid cached response: {}", e)))?;
+        Ok(Response::from(response))
+    }
+}
+
+fn stored_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    STORED_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = headers.get(name)?.to_str().ok()?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn body_hash(body: &[u8]) -> String {
+    format!("{:016x}", fnv1a(FNV_OFFSET, body))
+}
+
+/// Writes `contents` to a temporary file next to `path`, then renames it into place
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    static NEXT_TEMP: AtomicUsize = AtomicUsize::new(0);
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = PathBuf::from(temp);
+
+    let written = std::fs::write(&temp, contents).and_then(|_| std::fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    written.map_err(TextGenError::Io)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::HTTP_CLIENT;
+
+    fn temp_cache(name: &str) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!(
+            "text-gen-http-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        ResponseCache::new(dir)
+    }
+
+    #[test]
+    fn test_key_depends_on_url_and_auth() {
+        let plain = HTTP_CLIENT.get("https://example.com/a").build().unwrap();
+        let same = HTTP_CLIENT.get("https://example.com/a").build().unwrap();
+        let other = HTTP_CLIENT.get("https://example.com/b").build().unwrap();
+        let authorized = HTTP_CLIENT
+            .get("https://example.com/a")
+            .header(AUTHORIZATION, "Bearer token")
+            .build()
+            .unwrap();
+
+        assert_eq!(ResponseCache::key(&plain), ResponseCache::key(&same));
+        assert_ne!(ResponseCache::key(&plain), ResponseCache::key(&other));
+        assert_ne!(ResponseCache::key(&plain), ResponseCache::key(&authorized));
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let cache = temp_cache("round-trip");
+        let entry = CachedResponse {
+            url: "https://example.com/".to_string(),
+            final_url: Some("https://example.com/home".to_string()),
+            status: 200,
+            headers: vec![("etag".to_string(), "\"v1\"".to_string())],
+            stored_at: now_secs(),
+            body: b"hello".to_vec(),
+        };
+
+        cache.put("abc", &entry).unwrap();
+        let cached = cache.get("abc", "https://example.com/").unwrap();
+
+        assert_eq!(cached, entry);
+        assert!(cached.is_fresh(Duration::from_secs(60)));
+        assert!(!cached.is_fresh(Duration::ZERO));
+        assert!(cache.get("abc", "https://example.com/other").is_none());
+
+        let response = cached.to_response().unwrap();
+        assert_eq!(response.url().as_str(), "https://example.com/home");
+        assert_eq!(response.headers()[ETAG], "\"v1\"");
+        assert_eq!(response.text().await.unwrap(), "hello");
+
+        // Only the entry's two files are left behind
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 2);
+
+        // A body from another write is not served with these headers
+        std::fs::write(cache.body_path("abc"), b"goodbye").unwrap();
+        assert!(cache.get("abc", "https://example.com/").is_none());
+
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
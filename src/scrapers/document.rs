@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+
+/// A single piece of scraped text (a song, an article, a page) with its metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    pub text: String,
+    pub title: Option<String>,
+    /// URL the text was fetched from
+    pub source: Option<String>,
+    pub author: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    /// ISO 639-1 code, when the source reports one
+    pub language: Option<String>,
+}
+
+impl Document {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            title: None,
+            source: None,
+            author: None,
+            fetched_at: Utc::now(),
+            language: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Heading shown above the text: "author - title", or whichever is known
+    pub fn heading(&self) -> Option<String> {
+        match (&self.author, &self.title) {
+            (Some(author), Some(title)) => Some(format!("{} - {}", author, title)),
+            (None, Some(title)) => Some(title.clone()),
+            _ => None,
+        }
+    }
+
+    /// The text with its heading as a `# ` line, as it appears in joined output
+    pub fn to_text(&self) -> String {
+        match self.heading() {
+            Some(heading) => format!("# {}\n\n{}", heading, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// Joins documents into one text, separating them with blank lines
+pub fn join_documents(documents: &[Document]) -> String {
+    documents
+        .iter()
+        .map(Document::to_text)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -1,9 +1,19 @@
+pub mod cache;
+pub mod document;
 pub mod http;
 pub mod providers;
 pub mod registry;
+pub mod request;
+pub mod robots;
 pub mod scraper_trait;
+pub mod sitemap;
 
 pub use crate::config::ScraperConfig;
-pub use http::HTTP_CLIENT;
-pub use registry::{get_scraper_interactive, load_scraper_from_config};
+pub use cache::ResponseCache;
+pub use document::{Document, join_documents};
+pub use http::{HTTP_CLIENT, USER_AGENT};
+pub use registry::{configure_interactive, get_scraper_interactive, load_scraper_from_config};
+pub use request::{RequestClient, RequestPolicy};
+pub use robots::RobotsRules;
 pub use scraper_trait::Scraper;
+pub use sitemap::{Sitemap, SitemapEntry};
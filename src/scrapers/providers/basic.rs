@@ -1,25 +1,54 @@
 use crate::config::ScraperConfig;
 use crate::error::{Result, TextGenError};
-use crate::scrapers::HTTP_CLIENT;
 use crate::scrapers::scraper_trait::Scraper;
+use crate::scrapers::sitemap::{self, MAX_SITEMAP_BYTES, Sitemap};
+use crate::scrapers::{Document, HTTP_CLIENT, RequestClient, RequestPolicy, join_documents};
 
 use async_trait::async_trait;
-use log::{info, warn};
-use once_cell::sync::OnceCell;
-use scraper::Selector;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use log::{debug, info, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use reqwest::Url;
+use scraper::{Html, Selector};
 use serde_json::json;
+use std::collections::HashSet;
 use std::io::{self, BufRead};
+use std::sync::Arc;
 use std::time::Duration;
 
 const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
 
 const MAX_HTML_SIZE_BYTES: usize = 10 * 1024 * 1024; // 10MB
 
+/// Page limit when crawling or reading a sitemap
+const DEFAULT_MAX_PAGES: usize = 50;
+
+/// How deep sitemap indexes may nest
+const MAX_SITEMAP_DEPTH: usize = 3;
+
+static LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| Selector::parse("a[href]").unwrap());
+static TITLE_SELECTOR: Lazy<Selector> = Lazy::new(|| Selector::parse("title").unwrap());
+
 pub struct BasicScraper {
     url: String,
     selector: String,
     request_timeout: Duration,
+    requests: Arc<RequestClient>,
     selector_cache: OnceCell<Selector>,
+    max_depth: usize,
+    max_pages: usize,
+    link_pattern: Option<Regex>,
+    sitemap: Option<String>,
+    modified_since: Option<DateTime<Utc>>,
+}
+
+/// Text, title and links extracted from one fetched page
+struct Page {
+    text: String,
+    title: Option<String>,
+    links: Vec<Url>,
 }
 
 impl BasicScraper {
@@ -28,7 +57,13 @@ impl BasicScraper {
             url,
             selector,
             request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            requests: Arc::new(RequestClient::default().with_robots(true)),
             selector_cache: OnceCell::new(),
+            max_depth: 0,
+            max_pages: DEFAULT_MAX_PAGES,
+            link_pattern: None,
+            sitemap: None,
+            modified_since: None,
         }
     }
 
@@ -37,7 +72,54 @@ impl BasicScraper {
         self
     }
 
-    pub fn interactive_config() -> Box<dyn Scraper> {
+    /// Sets concurrency, per-host rate limiting and retries for this scraper's requests
+    ///
+    /// Requests honour robots.txt unless the policy sets `ignore_robots`.
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.requests = Arc::new(RequestClient::new(policy).with_robots(true));
+        self
+    }
+
+    /// Follows same-domain links this many levels deep (0 fetches only the start page)
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Stops crawling once this many pages were fetched; failed pages do not count
+    ///
+    /// In sitemap mode this caps the number of listed URLs instead.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Fetches the pages listed in this sitemap (or sitemap index) instead of crawling
+    ///
+    /// `max_pages`, the link pattern and [`with_modified_since`](Self::with_modified_since)
+    /// filter the listed URLs.
+    pub fn with_sitemap(mut self, sitemap_url: impl Into<String>) -> Self {
+        self.sitemap = Some(sitemap_url.into());
+        self
+    }
+
+    /// Skips sitemap entries whose `lastmod` is older than `since`
+    pub fn with_modified_since(mut self, since: DateTime<Utc>) -> Self {
+        self.modified_since = Some(since);
+        self
+    }
+
+    /// Only follows links (or sitemap entries) whose URL matches `pattern`
+    pub fn with_link_pattern(mut self, pattern: &str) -> Result<Self> {
+        let pattern = Regex::new(pattern).map_err(|e| {
+            TextGenError::Config(format!("Invalid link pattern '{}': {}", pattern, e))
+        })?;
+        self.link_pattern = Some(pattern);
+        Ok(self)
+    }
+
+    /// Asks for the settings on stdin and saves them for next time
+    pub fn interactive_config() -> ScraperConfig {
         println!("-- Basic Scraper Config --");
         println!("Enter URL to scrape:");
         let stdin = io::stdin();
@@ -55,6 +137,12 @@ impl BasicScraper {
             selector
         };
 
+        println!("Enter crawl depth for same-site links (default: 0, only this page):");
+        let mut depth_input = String::new();
+        stdin.lock().read_line(&mut depth_input).unwrap();
+
+        let max_depth = depth_input.trim().parse::<usize>().unwrap_or(0);
+
         println!("Enter request timeout in seconds (default: 30):");
         let mut timeout_input = String::new();
         stdin.lock().read_line(&mut timeout_input).unwrap();
@@ -69,6 +157,7 @@ impl BasicScraper {
             settings: json!({
                 "url": url.trim(),
                 "selector": selector,
+                "max_depth": max_depth,
                 "request_timeout": request_timeout,
             }),
         };
@@ -77,13 +166,10 @@ impl BasicScraper {
             warn!("Failed to save config: {}", e);
         }
 
-        Box::new(
-            BasicScraper::new(url.trim().to_string(), selector.to_string())
-                .with_request_timeout(request_timeout),
-        )
+        config
     }
 
-    pub fn from_config(settings: &serde_json::Value) -> Box<dyn Scraper> {
+    pub fn from_config(settings: &serde_json::Value) -> Result<Box<dyn Scraper>> {
         let url = settings["url"].as_str().unwrap_or_default().to_string();
         let selector_str = settings["selector"].as_str().unwrap_or_default();
         let selector = if selector_str.is_empty() {
@@ -96,7 +182,34 @@ impl BasicScraper {
             .as_u64()
             .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
 
-        Box::new(BasicScraper::new(url, selector).with_request_timeout(request_timeout))
+        let mut scraper = BasicScraper::new(url, selector)
+            .with_max_depth(settings["max_depth"].as_u64().unwrap_or(0) as usize)
+            .with_max_pages(
+                settings["max_pages"]
+                    .as_u64()
+                    .map(|n| n as usize)
+                    .unwrap_or(DEFAULT_MAX_PAGES),
+            )
+            .with_request_timeout(request_timeout)
+            .with_request_policy(RequestPolicy::from_settings(settings));
+
+        if let Some(sitemap) = settings["sitemap"].as_str() {
+            scraper = scraper.with_sitemap(sitemap);
+        }
+        if let Some(since) = settings["modified_since"].as_str() {
+            let since = sitemap::parse_lastmod(since).ok_or_else(|| {
+                TextGenError::Config(format!(
+                    "Invalid modified_since date '{}', expected YYYY-MM-DD",
+                    since
+                ))
+            })?;
+            scraper = scraper.with_modified_since(since);
+        }
+        if let Some(pattern) = settings["link_pattern"].as_str() {
+            scraper = scraper.with_link_pattern(pattern)?;
+        }
+
+        Ok(Box::new(scraper))
     }
 
     fn get_selector(&self) -> Result<&Selector> {
@@ -106,40 +219,31 @@ impl BasicScraper {
             })
         })
     }
-}
-
-#[async_trait]
-impl Scraper for BasicScraper {
-    async fn fetch_text(&self) -> Result<String> {
-        let selector = self.get_selector()?;
 
-        info!("Fetching content from URL: {}", self.url);
-
-        let response = HTTP_CLIENT
-            .get(&self.url)
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| TextGenError::Http(e))?;
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let response = self
+            .requests
+            .send(HTTP_CLIENT.get(url).timeout(self.request_timeout))
+            .await?;
 
         if !response.status().is_success() {
             return Err(TextGenError::Scraper(format!(
                 "Failed to fetch content from {}: HTTP {}",
-                self.url,
+                url,
                 response.status()
             )));
         }
 
-        if let Some(length) = response.content_length() {
-            if length > MAX_HTML_SIZE_BYTES as u64 {
-                return Err(TextGenError::Scraper(format!(
-                    "HTML content too large: {} bytes (max: {} bytes)",
-                    length, MAX_HTML_SIZE_BYTES
-                )));
-            }
+        if let Some(length) = response.content_length()
+            && length > MAX_HTML_SIZE_BYTES as u64
+        {
+            return Err(TextGenError::Scraper(format!(
+                "HTML content too large: {} bytes (max: {} bytes)",
+                length, MAX_HTML_SIZE_BYTES
+            )));
         }
 
-        let html = response.text().await.map_err(|e| TextGenError::Http(e))?;
+        let html = response.text().await.map_err(TextGenError::Http)?;
 
         if html.len() > MAX_HTML_SIZE_BYTES {
             return Err(TextGenError::Scraper(format!(
@@ -149,25 +253,288 @@ impl Scraper for BasicScraper {
             )));
         }
 
-        let document = scraper::Html::parse_document(&html);
-        let mut extracted_text = String::new();
+        Ok(html)
+    }
 
+    /// Fetches a page and extracts its text, and its links when they will be followed
+    async fn fetch_page(&self, url: &Url, follow_links: bool) -> Result<Page> {
+        let html = self.fetch_html(url.as_str()).await?;
+        let selector = self.get_selector()?;
+        let document = Html::parse_document(&html);
+
+        let mut text = String::new();
         for element in document.select(selector) {
-            let text = element.text().collect::<Vec<_>>().join(" ");
-            if !text.trim().is_empty() {
-                extracted_text.push_str(&text);
-                extracted_text.push('\n');
+            let element_text = element.text().collect::<Vec<_>>().join(" ");
+            if !element_text.trim().is_empty() {
+                text.push_str(&element_text);
+                text.push('\n');
             }
         }
 
-        if extracted_text.is_empty() {
-            return Err(TextGenError::Scraper(format!(
+        let title = document
+            .select(&TITLE_SELECTOR)
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+            .filter(|title| !title.is_empty());
+
+        let links = if follow_links {
+            extract_links(&document, url, self.link_pattern.as_ref())
+        } else {
+            Vec::new()
+        };
+
+        Ok(Page { text, title, links })
+    }
+
+    /// Breadth-first crawl of same-site pages from the start URL
+    async fn crawl(&self, start: Url) -> Result<Vec<Document>> {
+        let mut visited = HashSet::from([start.to_string()]);
+        let mut frontier = vec![start];
+        let mut documents = Vec::new();
+        let mut fetched = 0;
+
+        for depth in 0..=self.max_depth {
+            if frontier.is_empty() || fetched >= self.max_pages {
+                break;
+            }
+            info!("Crawling up to {} pages at depth {}", frontier.len(), depth);
+
+            let follow_links = depth < self.max_depth;
+            let mut next = Vec::new();
+            let mut queue = frontier.into_iter();
+
+            // Failed pages do not count toward max_pages, so refill from the queue
+            while fetched < self.max_pages {
+                let batch: Vec<Url> = queue.by_ref().take(self.max_pages - fetched).collect();
+                if batch.is_empty() {
+                    break;
+                }
+                let pages = join_all(
+                    batch
+                        .iter()
+                        .map(|url| async move { (url, self.fetch_page(url, follow_links).await) }),
+                )
+                .await;
+
+                for (url, page) in pages {
+                    let page = match page {
+                        Ok(page) => page,
+                        Err(e) => {
+                            warn!("Skipping {}: {}", url, e);
+                            continue;
+                        }
+                    };
+                    fetched += 1;
+
+                    for link in &page.links {
+                        if visited.insert(link.to_string()) {
+                            next.push(link.clone());
+                        }
+                    }
+
+                    documents.extend(self.page_document(url, page));
+                }
+            }
+            frontier = next;
+        }
+
+        info!("Crawled {} pages, {} with text", fetched, documents.len());
+        Ok(documents)
+    }
+
+    /// The page as a document, or `None` when the selector found no text
+    fn page_document(&self, url: &Url, page: Page) -> Option<Document> {
+        if page.text.trim().is_empty() {
+            debug!(
                 "No text found using selector '{}' at {}",
-                self.selector, self.url
+                self.selector, url
+            );
+            return None;
+        }
+        let mut document = Document::new(page.text).with_source(url.as_str());
+        if let Some(title) = page.title {
+            document = document.with_title(title);
+        }
+        Some(document)
+    }
+
+    async fn fetch_sitemap(&self, url: &str) -> Result<Sitemap> {
+        let response = self
+            .requests
+            .send(HTTP_CLIENT.get(url).timeout(self.request_timeout))
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(TextGenError::Scraper(format!(
+                "Failed to fetch sitemap {}: HTTP {}",
+                url,
+                response.status()
             )));
         }
 
-        Ok(extracted_text)
+        let bytes = response.bytes().await.map_err(TextGenError::Http)?;
+        if bytes.len() > MAX_SITEMAP_BYTES {
+            return Err(TextGenError::Scraper(format!(
+                "Sitemap too large: {} bytes (max: {} bytes)",
+                bytes.len(),
+                MAX_SITEMAP_BYTES
+            )));
+        }
+        sitemap::parse_sitemap(&bytes)
+    }
+
+    /// Page URLs listed in a sitemap, following nested sitemap indexes
+    ///
+    /// Entries are filtered by `lastmod` and the link pattern, deduplicated and
+    /// capped at `max_pages`. Unreadable nested sitemaps are skipped.
+    async fn sitemap_urls(&self, sitemap_url: &str) -> Result<Vec<Url>> {
+        let mut pending = vec![(sitemap_url.to_string(), 0)];
+        let mut seen = HashSet::new();
+        let mut urls = Vec::new();
+
+        while let Some((current, depth)) = pending.pop() {
+            let sitemap = match self.fetch_sitemap(&current).await {
+                Ok(sitemap) => sitemap,
+                Err(e) if current != sitemap_url => {
+                    warn!("Skipping sitemap {}: {}", current, e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let modified = |entry: &sitemap::SitemapEntry| {
+                self.modified_since
+                    .is_none_or(|since| entry.modified_since(since))
+            };
+
+            match sitemap {
+                Sitemap::Index(sitemaps) if depth < MAX_SITEMAP_DEPTH => {
+                    // Reversed so nested sitemaps are read in listed order
+                    pending.extend(
+                        sitemaps
+                            .into_iter()
+                            .rev()
+                            .filter(|entry| modified(entry))
+                            .map(|entry| (entry.loc, depth + 1)),
+                    );
+                }
+                Sitemap::Index(_) => warn!("Sitemap indexes nested too deeply at {}", current),
+                Sitemap::Urls(entries) => {
+                    for entry in entries.into_iter().filter(|entry| modified(entry)) {
+                        let Ok(url) = Url::parse(&entry.loc) else {
+                            debug!("Skipping invalid sitemap URL '{}'", entry.loc);
+                            continue;
+                        };
+                        let wanted = self
+                            .link_pattern
+                            .as_ref()
+                            .is_none_or(|pattern| pattern.is_match(url.as_str()));
+                        if wanted && seen.insert(url.to_string()) {
+                            urls.push(url);
+                        }
+                        if urls.len() >= self.max_pages {
+                            return Ok(urls);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(urls)
+    }
+
+    /// Fetches every page listed in the sitemap
+    async fn fetch_sitemap_pages(&self, sitemap_url: &str) -> Result<Vec<Document>> {
+        info!(
+            "Reading sitemap {} (up to {} pages)",
+            sitemap_url, self.max_pages
+        );
+        let urls = self.sitemap_urls(sitemap_url).await?;
+        info!("Fetching {} pages listed in the sitemap", urls.len());
+
+        let pages = join_all(
+            urls.iter()
+                .map(|url| async move { (url, self.fetch_page(url, false).await) }),
+        )
+        .await;
+
+        let documents: Vec<Document> = pages
+            .into_iter()
+            .filter_map(|(url, page)| match page {
+                Ok(page) => self.page_document(url, page),
+                Err(e) => {
+                    warn!("Skipping {}: {}", url, e);
+                    None
+                }
+            })
+            .collect();
+
+        if documents.is_empty() {
+            return Err(TextGenError::Scraper(format!(
+                "No text found using selector '{}' on any page listed in {}",
+                self.selector, sitemap_url
+            )));
+        }
+        Ok(documents)
+    }
+}
+
+/// Absolute same-host links of a page, without fragments, optionally filtered by `pattern`
+pub fn extract_links(document: &Html, base: &Url, pattern: Option<&Regex>) -> Vec<Url> {
+    let mut seen = HashSet::new();
+
+    document
+        .select(&LINK_SELECTOR)
+        .filter_map(|link| base.join(link.value().attr("href")?).ok())
+        .filter_map(|mut url| {
+            url.set_fragment(None);
+            let same_site = matches!(url.scheme(), "http" | "https")
+                && url.host_str() == base.host_str()
+                && url.port_or_known_default() == base.port_or_known_default();
+            let wanted = pattern.is_none_or(|pattern| pattern.is_match(url.as_str()));
+            (same_site && wanted && seen.insert(url.to_string())).then_some(url)
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Scraper for BasicScraper {
+    async fn fetch_text(&self) -> Result<String> {
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        self.get_selector()?;
+        if let Some(sitemap_url) = &self.sitemap {
+            return self.fetch_sitemap_pages(sitemap_url).await;
+        }
+
+        let start = Url::parse(&self.url)
+            .map_err(|e| TextGenError::Config(format!("Invalid URL '{}': {}", self.url, e)))?;
+
+        if self.max_depth == 0 {
+            info!("Fetching content from URL: {}", self.url);
+            let page = self.fetch_page(&start, false).await?;
+            if page.text.is_empty() {
+                return Err(TextGenError::Scraper(format!(
+                    "No text found using selector '{}' at {}",
+                    self.selector, self.url
+                )));
+            }
+            return Ok(vec![Document::new(page.text).with_source(&self.url)]);
+        }
+
+        info!(
+            "Crawling from {} (depth {}, up to {} pages)",
+            self.url, self.max_depth, self.max_pages
+        );
+        let documents = self.crawl(start).await?;
+        if documents.is_empty() {
+            return Err(TextGenError::Scraper(format!(
+                "No text found using selector '{}' on any page crawled from {}",
+                self.selector, self.url
+            )));
+        }
+        Ok(documents)
     }
 }
 
@@ -177,7 +544,13 @@ impl Clone for BasicScraper {
             url: self.url.clone(),
             selector: self.selector.clone(),
             request_timeout: self.request_timeout,
+            requests: self.requests.clone(),
             selector_cache: OnceCell::new(),
+            max_depth: self.max_depth,
+            max_pages: self.max_pages,
+            link_pattern: self.link_pattern.clone(),
+            sitemap: self.sitemap.clone(),
+            modified_since: self.modified_since,
         }
     }
 }
@@ -0,0 +1,475 @@
+use crate::config::ScraperConfig;
+use crate::error::{Result, TextGenError};
+use crate::input::formats::html::extract_html;
+use crate::scrapers::scraper_trait::Scraper;
+use crate::scrapers::{Document, HTTP_CLIENT, RequestClient, RequestPolicy, cache, join_documents};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use log::{debug, info, warn};
+use once_cell::sync::OnceCell;
+use roxmltree::Node;
+use scraper::{Html, Selector};
+use serde_json::json;
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+const CONTENT_NAMESPACE: &str = "http://purl.org/rss/1.0/modules/content/";
+
+/// Fetches RSS 2.0 and Atom feeds, one document per entry
+///
+/// Entries are deduplicated across feeds by link. With an article selector,
+/// each entry's link is fetched and the selected elements replace the feed's
+/// (often truncated) content. Feeds are always revalidated with the server,
+/// since they change more often than the HTTP cache's TTL.
+pub struct FeedScraper {
+    feeds: Vec<String>,
+    article_selector: Option<String>,
+    max_entries: Option<usize>,
+    request_timeout: Duration,
+    requests: Arc<RequestClient>,
+    selector_cache: OnceCell<Option<Selector>>,
+}
+
+/// A parsed feed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Feed {
+    pub title: Option<String>,
+    pub language: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+/// One RSS item or Atom entry, with its content as plain text
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub content: String,
+}
+
+impl FeedScraper {
+    pub fn new(feeds: Vec<String>) -> Self {
+        Self {
+            feeds,
+            article_selector: None,
+            max_entries: None,
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            requests: Arc::new(Self::request_client(RequestPolicy::default())),
+            selector_cache: OnceCell::new(),
+        }
+    }
+
+    pub fn with_request_timeout(mut self, timeout_secs: u64) -> Self {
+        self.request_timeout = Duration::from_secs(timeout_secs);
+        self
+    }
+
+    /// Sets concurrency, per-host rate limiting and retries for this scraper's requests
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.requests = Arc::new(Self::request_client(policy));
+        self
+    }
+
+    /// Follows each entry's link and takes its full text from elements matching `selector`
+    pub fn with_article_selector(mut self, selector: impl Into<String>) -> Self {
+        self.article_selector = Some(selector.into());
+        self.selector_cache = OnceCell::new();
+        self
+    }
+
+    /// Keeps only the first `max_entries` entries of each feed
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Honours robots.txt, and revalidates cached responses on every run
+    fn request_client(policy: RequestPolicy) -> RequestClient {
+        let cache =
+            cache::shared().map(|cache| Arc::new(cache.as_ref().clone().with_ttl(Duration::ZERO)));
+        RequestClient::new(policy)
+            .with_cache(cache)
+            .with_robots(true)
+    }
+
+    /// Asks for the settings on stdin and saves them for next time
+    pub fn interactive_config() -> ScraperConfig {
+        println!("-- Feed Scraper Config --");
+        println!("Enter RSS or Atom feed URLs (comma-separated):");
+        let stdin = io::stdin();
+        let mut feeds_input = String::new();
+        stdin.lock().read_line(&mut feeds_input).unwrap();
+
+        let feeds: Vec<String> = feeds_input
+            .trim()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        println!(
+            "Enter CSS selector to fetch full articles from entry links (optional, press Enter to use feed content):"
+        );
+        let mut selector = String::new();
+        stdin.lock().read_line(&mut selector).unwrap();
+        let selector = selector.trim();
+
+        println!("Enter request timeout in seconds (default: 30):");
+        let mut timeout_input = String::new();
+        stdin.lock().read_line(&mut timeout_input).unwrap();
+
+        let request_timeout = timeout_input
+            .trim()
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        let config = ScraperConfig {
+            scraper_type: "feed".to_string(),
+            settings: json!({
+                "feeds": feeds,
+                "selector": (!selector.is_empty()).then_some(selector),
+                "request_timeout": request_timeout,
+            }),
+        };
+
+        if let Err(e) = crate::config::save_interactive_config(&config) {
+            warn!("Failed to save config: {}", e);
+        }
+
+        config
+    }
+
+    pub fn from_config(settings: &serde_json::Value) -> Result<Box<dyn Scraper>> {
+        let mut feeds: Vec<String> = settings["feeds"]
+            .as_array()
+            .map(|feeds| {
+                feeds
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(url) = settings["url"].as_str() {
+            feeds.push(url.to_string());
+        }
+
+        let request_timeout = settings["request_timeout"]
+            .as_u64()
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        let mut scraper = FeedScraper::new(feeds)
+            .with_request_timeout(request_timeout)
+            .with_request_policy(RequestPolicy::from_settings(settings));
+
+        if let Some(selector) = settings["selector"].as_str().filter(|s| !s.is_empty()) {
+            scraper = scraper.with_article_selector(selector);
+        }
+        if let Some(max_entries) = settings["max_entries"].as_u64() {
+            scraper = scraper.with_max_entries(max_entries as usize);
+        }
+
+        Ok(Box::new(scraper))
+    }
+
+    fn get_selector(&self) -> Result<Option<&Selector>> {
+        self.selector_cache
+            .get_or_try_init(|| {
+                self.article_selector
+                    .as_deref()
+                    .map(|selector| {
+                        Selector::parse(selector).map_err(|e| {
+                            TextGenError::Scraper(format!(
+                                "Invalid CSS selector '{}': {}",
+                                selector, e
+                            ))
+                        })
+                    })
+                    .transpose()
+            })
+            .map(Option::as_ref)
+    }
+
+    async fn fetch_body(&self, url: &str) -> Result<String> {
+        let response = self
+            .requests
+            .send(HTTP_CLIENT.get(url).timeout(self.request_timeout))
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(TextGenError::Scraper(format!(
+                "Failed to fetch {}: HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        response.text().await.map_err(TextGenError::Http)
+    }
+
+    async fn fetch_feed(&self, url: &str) -> Result<Feed> {
+        info!("Fetching feed {}", url);
+        let mut feed = parse_feed(&self.fetch_body(url).await?)?;
+        if let Some(max_entries) = self.max_entries {
+            feed.entries.truncate(max_entries);
+        }
+        debug!("{} entries in {}", feed.entries.len(), url);
+        Ok(feed)
+    }
+
+    /// Text of the elements matching `selector` on the entry's page
+    async fn fetch_article(&self, link: &str, selector: &Selector) -> Result<String> {
+        let html = self.fetch_body(link).await?;
+        let document = Html::parse_document(&html);
+        let text = document
+            .select(selector)
+            .map(|element| extract_html(&element.html()).text)
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        if text.is_empty() {
+            return Err(TextGenError::Scraper(format!(
+                "No text found using selector '{}' at {}",
+                self.article_selector.as_deref().unwrap_or_default(),
+                link
+            )));
+        }
+        Ok(text)
+    }
+
+    /// Builds an entry's document, fetching the full article when a selector is set
+    async fn entry_document(
+        &self,
+        feed_url: &str,
+        language: Option<&str>,
+        entry: &FeedEntry,
+        selector: Option<&Selector>,
+    ) -> Option<Document> {
+        let mut text = entry.content.clone();
+        if let (Some(selector), Some(link)) = (selector, entry.link.as_deref()) {
+            match self.fetch_article(link, selector).await {
+                Ok(article) => text = article,
+                Err(e) => warn!("Using feed content for {}: {}", link, e),
+            }
+        }
+        if text.trim().is_empty() {
+            debug!("Skipping empty entry {:?} in {}", entry.title, feed_url);
+            return None;
+        }
+
+        let mut document =
+            Document::new(text).with_source(entry.link.as_deref().unwrap_or(feed_url));
+        if let Some(title) = &entry.title {
+            document = document.with_title(title);
+        }
+        if let Some(author) = &entry.author {
+            document = document.with_author(author);
+        }
+        if let Some(language) = language {
+            document = document.with_language(language);
+        }
+        Some(document)
+    }
+}
+
+#[async_trait]
+impl Scraper for FeedScraper {
+    async fn fetch_text(&self) -> Result<String> {
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        if self.feeds.is_empty() {
+            return Err(TextGenError::Scraper("No feeds specified".to_string()));
+        }
+        let selector = self.get_selector()?;
+
+        let feeds = join_all(self.feeds.iter().map(|url| async move {
+            let feed = self.fetch_feed(url).await;
+            if let Err(e) = &feed {
+                warn!("Skipping feed {}: {}", url, e);
+            }
+            (url, feed)
+        }))
+        .await;
+
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for (url, feed) in &feeds {
+            let Ok(feed) = feed else { continue };
+            let language = feed.language.as_deref();
+            for entry in &feed.entries {
+                let key = entry.link.clone().unwrap_or_else(|| entry.content.clone());
+                if seen.insert(key) {
+                    entries.push((url.as_str(), language, entry));
+                }
+            }
+        }
+
+        info!("Reading {} feed entries", entries.len());
+        let documents: Vec<Document> = join_all(
+            entries
+                .into_iter()
+                .map(|(url, language, entry)| self.entry_document(url, language, entry, selector)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if documents.is_empty() {
+            return Err(TextGenError::Scraper(
+                "No feed entries were successfully fetched".to_string(),
+            ));
+        }
+        Ok(documents)
+    }
+}
+
+impl Clone for FeedScraper {
+    fn clone(&self) -> Self {
+        Self {
+            feeds: self.feeds.clone(),
+            article_selector: self.article_selector.clone(),
+            max_entries: self.max_entries,
+            request_timeout: self.request_timeout,
+            requests: self.requests.clone(),
+            selector_cache: OnceCell::new(),
+        }
+    }
+}
+
+/// Parses an RSS 2.0 or Atom feed, converting entry HTML to plain text
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| TextGenError::Scraper(format!("Invalid feed XML: {}", e)))?;
+    let root = document.root_element();
+
+    match root.tag_name().name() {
+        "rss" => {
+            let channel = child(root, "channel")
+                .ok_or_else(|| TextGenError::Scraper("RSS feed has no <channel>".to_string()))?;
+            Ok(Feed {
+                title: child_text(channel, "title").map(clean_line),
+                language: child_text(channel, "language").map(language_code),
+                entries: children(channel, "item").map(parse_rss_item).collect(),
+            })
+        }
+        "feed" => Ok(Feed {
+            title: child(root, "title").map(atom_text).map(|t| clean_line(&t)),
+            language: root
+                .attribute((roxmltree::NS_XML_URI, "lang"))
+                .map(language_code),
+            entries: children(root, "entry").map(parse_atom_entry).collect(),
+        }),
+        other => Err(TextGenError::Scraper(format!(
+            "Unexpected feed root element <{}>, expected <rss> or <feed>",
+            other
+        ))),
+    }
+}
+
+fn parse_rss_item(item: Node) -> FeedEntry {
+    // Full content, when the feed has it, is richer than the description
+    let content = item
+        .children()
+        .find(|node| node.has_tag_name((CONTENT_NAMESPACE, "encoded")))
+        .and_then(|node| node.text())
+        .or_else(|| child_text(item, "description"))
+        .map(html_to_text)
+        .unwrap_or_default();
+
+    FeedEntry {
+        title: child_text(item, "title").map(clean_line),
+        link: child_text(item, "link")
+            .or_else(|| child_text(item, "guid"))
+            .filter(|link| link.starts_with("http"))
+            .map(str::to_string),
+        author: child_text(item, "creator")
+            .or_else(|| child_text(item, "author"))
+            .map(clean_line),
+        published: child_text(item, "pubDate")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        content,
+    }
+}
+
+fn parse_atom_entry(entry: Node) -> FeedEntry {
+    let link = children(entry, "link")
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|link| link.attribute("href"))
+        .map(str::to_string);
+
+    FeedEntry {
+        title: child(entry, "title").map(atom_text).map(|t| clean_line(&t)),
+        link,
+        author: child(entry, "author")
+            .and_then(|author| child_text(author, "name"))
+            .map(clean_line),
+        published: child_text(entry, "published")
+            .or_else(|| child_text(entry, "updated"))
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        content: child(entry, "content")
+            .or_else(|| child(entry, "summary"))
+            .map(atom_text)
+            .unwrap_or_default(),
+    }
+}
+
+/// Plain text of an Atom text construct (`type` text, html or xhtml)
+fn atom_text(node: Node) -> String {
+    match node.attribute("type") {
+        Some("html") => html_to_text(node.text().unwrap_or_default()),
+        Some("xhtml") => {
+            let markup = &node.document().input_text()[node.range()];
+            html_to_text(markup)
+        }
+        _ => node.text().unwrap_or_default().trim().to_string(),
+    }
+}
+
+fn html_to_text(html: &str) -> String {
+    extract_html(html).text
+}
+
+fn clean_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// "en-us" -> "en"
+fn language_code(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(language)
+        .trim()
+        .to_lowercase()
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &'static str) -> Option<&'a str> {
+    child(node, name)?
+        .text()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
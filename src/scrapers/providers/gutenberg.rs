@@ -0,0 +1,545 @@
+use crate::config::ScraperConfig;
+use crate::error::{Result, TextGenError};
+use crate::input::compression;
+use crate::scrapers::scraper_trait::Scraper;
+use crate::scrapers::{Document, HTTP_CLIENT, RequestClient, RequestPolicy, join_documents};
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+pub const DEFAULT_MIRROR: &str = "https://www.gutenberg.org";
+
+/// Chapters with fewer words are table-of-contents entries, not chapters
+const MIN_CHAPTER_WORDS: usize = 20;
+
+/// Front-matter paragraphs with fewer words are title-page lines
+const MIN_PROSE_WORDS: usize = 12;
+
+static START_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?im)^\s*(\*{3}\s*START OF (THE|THIS) PROJECT GUTENBERG E-?BOOK.*|\*END\*THE SMALL PRINT!.*)$",
+    )
+    .unwrap()
+});
+static END_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?im)^\s*(\*{3}\s*END OF (THE|THIS) PROJECT GUTENBERG E-?BOOK|END OF (THE )?PROJECT GUTENBERG'?S?\b)",
+    )
+    .unwrap()
+});
+static METADATA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^(Title|Author|Language):[ \t]*(.+?)\s*$").unwrap());
+static PARAGRAPH_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n[ \t]*\n").unwrap());
+static CHAPTER_HEADING: Lazy<Regex> = Lazy::new(|| {
+    let numbers = "one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve|\
+                   thirteen|fourteen|fifteen|sixteen|seventeen|eighteen|nineteen";
+    let tens = "twenty|thirty|forty|fifty|sixty|seventy|eighty|ninety";
+    Regex::new(&format!(
+        r"(?i)^(chapter|book|part|volume|stave|letter)\s+([ivxlcdm]+|\d+|(({tens})([- ]({numbers}))?)|{numbers}|the\s+\w+)\b"
+    ))
+    .unwrap()
+});
+static CONTENTS_HEADING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(table of )?contents\.?$").unwrap());
+/// Bracketed transcriber's notes and illustration placeholders
+static INLINE_NOTES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\[\s*(transcriber[’']?s?\s+notes?|illustration)[^\]]*\]").unwrap()
+});
+static NOTE_HEADING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^transcriber[’']?s?\s+notes?\b").unwrap());
+static NOTE_ITEM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^([-*•]|p\.\s*\d|page\s+\d)").unwrap());
+static CREDITS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^(produced by|e-?text prepared by|transcribed (from|by)|updated editions will)",
+    )
+    .unwrap()
+});
+
+/// Where a book comes from
+#[derive(Clone, Debug, PartialEq)]
+pub enum GutenbergSource {
+    /// A downloaded plain-text book, possibly compressed; read without network access
+    File(PathBuf),
+    /// A book number, fetched as plain text from the mirror
+    Book(u64),
+}
+
+/// Project Gutenberg books without the license boilerplate, one document per chapter
+pub struct GutenbergScraper {
+    sources: Vec<GutenbergSource>,
+    split_chapters: bool,
+    mirror: String,
+    request_timeout: Duration,
+    requests: Arc<RequestClient>,
+}
+
+/// A book's text between the START and END markers, split into chapters
+#[derive(Clone, Debug, PartialEq)]
+pub struct GutenbergBook {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// ISO 639-1 code, from the header's `Language:` line
+    pub language: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    /// "CHAPTER I. Down the Rabbit-Hole"; `None` for front matter or unsplit books
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+impl GutenbergSource {
+    /// Reads a setting value: a book number, or anything else as a file path
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        if let Some(id) = value.as_u64() {
+            return Some(Self::Book(id));
+        }
+        let value = value.as_str()?.trim();
+        if value.is_empty() {
+            return None;
+        }
+        Some(match value.parse::<u64>() {
+            Ok(id) => Self::Book(id),
+            Err(_) => Self::File(PathBuf::from(value)),
+        })
+    }
+}
+
+impl GutenbergScraper {
+    pub fn new(sources: Vec<GutenbergSource>) -> Self {
+        Self {
+            sources,
+            split_chapters: true,
+            mirror: DEFAULT_MIRROR.to_string(),
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            requests: Arc::new(RequestClient::default()),
+        }
+    }
+
+    pub fn with_request_timeout(mut self, timeout_secs: u64) -> Self {
+        self.request_timeout = Duration::from_secs(timeout_secs);
+        self
+    }
+
+    /// Sets concurrency, per-host rate limiting and retries for this scraper's requests
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.requests = Arc::new(RequestClient::new(policy));
+        self
+    }
+
+    /// Emits one document per book instead of one per chapter
+    pub fn with_split_chapters(mut self, split_chapters: bool) -> Self {
+        self.split_chapters = split_chapters;
+        self
+    }
+
+    /// Downloads books from a Project Gutenberg mirror instead of the main site
+    pub fn with_mirror(mut self, mirror: impl Into<String>) -> Self {
+        self.mirror = mirror.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Asks for the settings on stdin and saves them for next time
+    pub fn interactive_config() -> ScraperConfig {
+        println!("-- Project Gutenberg Config --");
+        println!("Enter book numbers or paths to downloaded .txt files (comma-separated):");
+        let stdin = io::stdin();
+        let mut books_input = String::new();
+        stdin.lock().read_line(&mut books_input).unwrap();
+
+        let books: Vec<String> = books_input
+            .trim()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        println!("Split books into one document per chapter? (Y/n):");
+        let mut split_input = String::new();
+        stdin.lock().read_line(&mut split_input).unwrap();
+        let split_chapters = !split_input.trim().eq_ignore_ascii_case("n");
+
+        let config = ScraperConfig {
+            scraper_type: "gutenberg".to_string(),
+            settings: json!({
+                "books": books,
+                "split_chapters": split_chapters,
+            }),
+        };
+
+        if let Err(e) = crate::config::save_interactive_config(&config) {
+            warn!("Failed to save config: {}", e);
+        }
+
+        config
+    }
+
+    /// Reads `books` (numbers or file paths), `book_id` and `file`
+    pub fn from_config(settings: &serde_json::Value) -> Result<Box<dyn Scraper>> {
+        let mut sources: Vec<GutenbergSource> = settings["books"]
+            .as_array()
+            .map(|books| {
+                books
+                    .iter()
+                    .filter_map(GutenbergSource::from_value)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(id) = settings["book_id"].as_u64() {
+            sources.push(GutenbergSource::Book(id));
+        }
+        if let Some(file) = settings["file"].as_str() {
+            sources.push(GutenbergSource::File(PathBuf::from(file)));
+        }
+
+        let request_timeout = settings["request_timeout"]
+            .as_u64()
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        let mut scraper = GutenbergScraper::new(sources)
+            .with_split_chapters(settings["split_chapters"].as_bool().unwrap_or(true))
+            .with_request_timeout(request_timeout)
+            .with_request_policy(RequestPolicy::from_settings(settings));
+        if let Some(mirror) = settings["mirror"].as_str() {
+            scraper = scraper.with_mirror(mirror);
+        }
+
+        Ok(Box::new(scraper))
+    }
+
+    /// URL of a book's UTF-8 plain-text edition
+    pub fn book_url(&self, id: u64) -> String {
+        format!("{}/cache/epub/{}/pg{}.txt", self.mirror, id, id)
+    }
+
+    /// Raw text of a book and where it came from
+    async fn read_source(&self, source: &GutenbergSource) -> Result<(String, String)> {
+        match source {
+            GutenbergSource::File(path) => {
+                debug!("Reading Gutenberg book from {}", path.display());
+                let owned = path.clone();
+                let text = tokio::task::spawn_blocking(move || compression::read_to_string(&owned))
+                    .await
+                    .map_err(|e| TextGenError::Unknown(e.to_string()))??;
+                Ok((text, path.display().to_string()))
+            }
+            GutenbergSource::Book(id) => {
+                let url = self.book_url(*id);
+                info!("Fetching Gutenberg book {} from {}", id, url);
+                let response = self
+                    .requests
+                    .send(HTTP_CLIENT.get(&url).timeout(self.request_timeout))
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(TextGenError::Scraper(format!(
+                        "Failed to fetch Gutenberg book {}: HTTP {}",
+                        id,
+                        response.status()
+                    )));
+                }
+                let text = response.text().await.map_err(TextGenError::Http)?;
+                Ok((text, url))
+            }
+        }
+    }
+
+    fn book_documents(&self, book: GutenbergBook, source: &str) -> Vec<Document> {
+        let chapters = if self.split_chapters {
+            book.chapters
+        } else {
+            let text = book
+                .chapters
+                .into_iter()
+                .map(|chapter| match chapter.heading {
+                    Some(heading) => format!("{}\n\n{}", heading, chapter.text),
+                    None => chapter.text,
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            vec![Chapter {
+                heading: None,
+                text,
+            }]
+        };
+
+        chapters
+            .into_iter()
+            .map(|chapter| {
+                let title = match (&book.title, chapter.heading) {
+                    (Some(title), Some(heading)) => Some(format!("{}: {}", title, heading)),
+                    (Some(title), None) => Some(title.clone()),
+                    (None, heading) => heading,
+                };
+                let mut document = Document::new(chapter.text).with_source(source);
+                if let Some(title) = title {
+                    document = document.with_title(title);
+                }
+                if let Some(author) = &book.author {
+                    document = document.with_author(author);
+                }
+                if let Some(language) = &book.language {
+                    document = document.with_language(language);
+                }
+                document
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Scraper for GutenbergScraper {
+    async fn fetch_text(&self) -> Result<String> {
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        if self.sources.is_empty() {
+            return Err(TextGenError::Scraper(
+                "No Gutenberg books or files specified".to_string(),
+            ));
+        }
+
+        let mut documents = Vec::new();
+        for source in &self.sources {
+            let (text, origin) = match self.read_source(source).await {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!("Skipping Gutenberg book {:?}: {}", source, e);
+                    continue;
+                }
+            };
+            let book = parse_book(&text);
+            info!(
+                "Read {} from {} ({} chapters)",
+                book.title.as_deref().unwrap_or("untitled book"),
+                origin,
+                book.chapters.len()
+            );
+            documents.extend(self.book_documents(book, &origin));
+        }
+
+        if documents.is_empty() {
+            return Err(TextGenError::Scraper(
+                "No Gutenberg books were successfully read".to_string(),
+            ));
+        }
+        Ok(documents)
+    }
+}
+
+impl Clone for GutenbergScraper {
+    fn clone(&self) -> Self {
+        Self {
+            sources: self.sources.clone(),
+            split_chapters: self.split_chapters,
+            mirror: self.mirror.clone(),
+            request_timeout: self.request_timeout,
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+/// Strips the Project Gutenberg header, license and notes from a book and splits it into chapters
+///
+/// Text outside the START/END markers is dropped (the whole text is used when
+/// they are missing), as are the table of contents, transcriber's notes,
+/// illustration placeholders and production credits. Hard-wrapped lines are
+/// joined into paragraphs. Text before the first chapter is kept as an
+/// unheaded chapter only if it contains prose, such as a preface.
+pub fn parse_book(text: &str) -> GutenbergBook {
+    let text = text.replace("\r\n", "\n");
+    let text = text.trim_start_matches('\u{feff}');
+
+    let (header, body) = match START_MARKER.find(text) {
+        Some(start) => (&text[..start.start()], &text[start.end()..]),
+        None => {
+            warn!("No Project Gutenberg START marker found, using the whole text");
+            ("", text)
+        }
+    };
+    let body = match END_MARKER.find(body) {
+        Some(end) => &body[..end.start()],
+        None => body,
+    };
+
+    let mut title = None;
+    let mut author = None;
+    let mut language = None;
+    for captures in METADATA.captures_iter(header) {
+        let value = captures[2].to_string();
+        match &captures[1] {
+            "Title" => title = Some(value),
+            "Author" => author = Some(value),
+            _ => language = language_code(&value),
+        }
+    }
+
+    let body = INLINE_NOTES.replace_all(body, "");
+    let paragraphs = strip_notes(
+        PARAGRAPH_BREAK
+            .split(&body)
+            .map(|paragraph| paragraph.trim_matches('\n'))
+            .filter(|paragraph| !paragraph.trim().is_empty())
+            .filter(|paragraph| !CREDITS.is_match(paragraph.trim_start()))
+            .collect(),
+    );
+
+    GutenbergBook {
+        title,
+        author,
+        language,
+        chapters: split_chapters(&paragraphs),
+    }
+}
+
+/// Drops "Transcriber's Note" paragraphs and the list items that follow them
+fn strip_notes(paragraphs: Vec<&str>) -> Vec<&str> {
+    let mut kept = Vec::with_capacity(paragraphs.len());
+    let mut in_notes = false;
+    for paragraph in paragraphs {
+        let trimmed = paragraph.trim_start();
+        if NOTE_HEADING.is_match(trimmed) {
+            in_notes = true;
+        } else if !(in_notes && NOTE_ITEM.is_match(trimmed)) {
+            in_notes = false;
+            kept.push(paragraph);
+        }
+    }
+    kept
+}
+
+fn split_chapters(paragraphs: &[&str]) -> Vec<Chapter> {
+    let mut sections: Vec<(Option<String>, Vec<&str>)> = vec![(None, Vec::new())];
+    let mut index = 0;
+    while index < paragraphs.len() {
+        let paragraph = paragraphs[index];
+        match chapter_heading(paragraph) {
+            Some(mut heading) => {
+                // A separate all-caps line after a bare "CHAPTER I." is its title
+                if !paragraph.trim().contains('\n')
+                    && let Some(next) = paragraphs.get(index + 1)
+                    && is_caps_title(next)
+                {
+                    heading = format!("{} {}", heading, join_lines(next));
+                    index += 1;
+                }
+                sections.push((Some(heading), Vec::new()));
+            }
+            None => sections.last_mut().unwrap().1.push(paragraph),
+        }
+        index += 1;
+    }
+
+    let mut chapters = Vec::new();
+    for (heading, paragraphs) in sections {
+        let paragraphs: Vec<&str> = match heading {
+            // Front matter: title page lines and the contents list are not prose
+            None => paragraphs
+                .into_iter()
+                .filter(|paragraph| is_front_matter_prose(paragraph))
+                .collect(),
+            Some(_) => paragraphs,
+        };
+        let words: usize = paragraphs
+            .iter()
+            .map(|paragraph| paragraph.split_whitespace().count())
+            .sum();
+        if words < MIN_CHAPTER_WORDS {
+            if let Some(heading) = &heading {
+                debug!("Dropping '{}', which has no text", heading);
+            }
+            continue;
+        }
+        chapters.push(Chapter {
+            heading,
+            text: paragraphs
+                .iter()
+                .map(|paragraph| join_lines(paragraph))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        });
+    }
+    chapters
+}
+
+/// The normalised heading if `paragraph` is a short chapter heading
+fn chapter_heading(paragraph: &str) -> Option<String> {
+    let lines: Vec<&str> = paragraph.lines().map(str::trim).collect();
+    let first = lines.first()?;
+    // Several heading lines in one paragraph are a contents list
+    let is_heading = lines.len() <= 3
+        && first.chars().count() <= 60
+        && CHAPTER_HEADING.is_match(first)
+        && !lines[1..].iter().any(|line| CHAPTER_HEADING.is_match(line))
+        && lines.iter().all(|line| line.chars().count() <= 80);
+    is_heading.then(|| join_lines(paragraph))
+}
+
+fn is_caps_title(paragraph: &str) -> bool {
+    let paragraph = paragraph.trim();
+    !paragraph.contains('\n')
+        && paragraph.chars().count() <= 60
+        && paragraph.chars().any(char::is_alphabetic)
+        && !paragraph.chars().any(char::is_lowercase)
+        && chapter_heading(paragraph).is_none()
+}
+
+fn is_front_matter_prose(paragraph: &str) -> bool {
+    let trimmed = paragraph.trim();
+    if CONTENTS_HEADING.is_match(trimmed) || paragraph.split_whitespace().count() < MIN_PROSE_WORDS
+    {
+        return false;
+    }
+    // Contents lists are mostly chapter-heading lines
+    let lines: Vec<&str> = trimmed.lines().map(str::trim).collect();
+    let heading_lines = lines
+        .iter()
+        .filter(|line| CHAPTER_HEADING.is_match(line))
+        .count();
+    heading_lines * 2 < lines.len()
+}
+
+/// Joins hard-wrapped lines into one line with single spaces
+fn join_lines(paragraph: &str) -> String {
+    paragraph.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Maps a `Language:` header value ("English", "French") to an ISO 639-1 code
+fn language_code(language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    let code = match language.as_str() {
+        "english" => "en",
+        "french" => "fr",
+        "german" => "de",
+        "spanish" => "es",
+        "italian" => "it",
+        "portuguese" => "pt",
+        "dutch" => "nl",
+        "finnish" => "fi",
+        "swedish" => "sv",
+        "danish" => "da",
+        "norwegian" => "no",
+        "russian" => "ru",
+        "polish" => "pl",
+        "latin" => "la",
+        "greek" => "el",
+        "chinese" => "zh",
+        "japanese" => "ja",
+        other if other.len() == 2 && other.chars().all(|c| c.is_ascii_lowercase()) => other,
+        _ => return None,
+    };
+    Some(code.to_string())
+}
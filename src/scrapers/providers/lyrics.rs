@@ -1,13 +1,14 @@
 use crate::config::ScraperConfig;
 use crate::error::{Result, TextGenError};
-use crate::scrapers::HTTP_CLIENT;
 use crate::scrapers::scraper_trait::Scraper;
+use crate::scrapers::{Document, HTTP_CLIENT, RequestClient, RequestPolicy, join_documents};
 
 use async_trait::async_trait;
 use futures::future::join_all;
 use log::{info, warn};
 use serde_json::json;
 use std::io::{self, BufRead};
+use std::sync::Arc;
 use std::time::Duration;
 
 const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
@@ -17,6 +18,7 @@ pub struct LyricsScraper {
     artists: Vec<String>,
     max_songs_per_artist: Option<usize>,
     request_timeout: Duration,
+    requests: Arc<RequestClient>,
 }
 
 impl LyricsScraper {
@@ -30,6 +32,7 @@ impl LyricsScraper {
             artists,
             max_songs_per_artist,
             request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            requests: Arc::new(RequestClient::default()),
         }
     }
 
@@ -38,7 +41,14 @@ impl LyricsScraper {
         self
     }
 
-    pub fn interactive_config() -> Box<dyn Scraper> {
+    /// Sets concurrency, per-host rate limiting and retries for this scraper's requests
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.requests = Arc::new(RequestClient::new(policy));
+        self
+    }
+
+    /// Asks for the settings on stdin and saves them for next time
+    pub fn interactive_config() -> ScraperConfig {
         println!("-- Lyrics Scraper Config --");
         println!("Enter Genius API token:");
         let stdin = io::stdin();
@@ -92,13 +102,10 @@ impl LyricsScraper {
             warn!("Failed to save config: {}", e);
         }
 
-        Box::new(
-            LyricsScraper::new(api_token.trim().to_string(), artists, max_songs_per_artist)
-                .with_request_timeout(request_timeout),
-        )
+        config
     }
 
-    pub fn from_config(settings: &serde_json::Value) -> Box<dyn Scraper> {
+    pub fn from_config(settings: &serde_json::Value) -> Result<Box<dyn Scraper>> {
         let api_token = settings["api_token"]
             .as_str()
             .unwrap_or_default()
@@ -121,10 +128,11 @@ impl LyricsScraper {
             .as_u64()
             .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
 
-        Box::new(
+        Ok(Box::new(
             LyricsScraper::new(api_token, artists, max_songs_per_artist)
-                .with_request_timeout(request_timeout),
-        )
+                .with_request_timeout(request_timeout)
+                .with_request_policy(RequestPolicy::from_settings(settings)),
+        ))
     }
 
     async fn search_artist(&self, artist_name: &str) -> Result<u64> {
@@ -133,13 +141,15 @@ impl LyricsScraper {
             urlencoding::encode(artist_name)
         );
 
-        let response = HTTP_CLIENT
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| TextGenError::Http(e))?;
+        let response = self
+            .requests
+            .send(
+                HTTP_CLIENT
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.api_token))
+                    .timeout(self.request_timeout),
+            )
+            .await?;
 
         if !response.status().is_success() {
             return Err(TextGenError::Scraper(format!(
@@ -149,7 +159,7 @@ impl LyricsScraper {
             )));
         }
 
-        let data: serde_json::Value = response.json().await.map_err(|e| TextGenError::Http(e))?;
+        let data: serde_json::Value = response.json().await.map_err(TextGenError::Http)?;
 
         let hits = data["response"]["hits"].as_array().ok_or_else(|| {
             TextGenError::Scraper(format!(
@@ -159,16 +169,15 @@ impl LyricsScraper {
         })?;
 
         for hit in hits {
-            if let Some(primary_artist) = hit["result"]["primary_artist"].as_object() {
-                if primary_artist["name"]
+            if let Some(primary_artist) = hit["result"]["primary_artist"].as_object()
+                && primary_artist["name"]
                     .as_str()
                     .map(|name| name.to_lowercase() == artist_name.to_lowercase())
                     .unwrap_or(false)
-                {
-                    return Ok(primary_artist["id"].as_u64().ok_or_else(|| {
-                        TextGenError::Scraper(format!("Artist ID not found for '{}'", artist_name))
-                    })?);
-                }
+            {
+                return primary_artist["id"].as_u64().ok_or_else(|| {
+                    TextGenError::Scraper(format!("Artist ID not found for '{}'", artist_name))
+                });
             }
         }
 
@@ -189,13 +198,15 @@ impl LyricsScraper {
                 artist_id, per_page, page
             );
 
-            let response = HTTP_CLIENT
-                .get(&url)
-                .header("Authorization", format!("Bearer {}", self.api_token))
-                .timeout(self.request_timeout)
-                .send()
-                .await
-                .map_err(|e| TextGenError::Http(e))?;
+            let response = self
+                .requests
+                .send(
+                    HTTP_CLIENT
+                        .get(&url)
+                        .header("Authorization", format!("Bearer {}", self.api_token))
+                        .timeout(self.request_timeout),
+                )
+                .await?;
 
             if !response.status().is_success() {
                 return Err(TextGenError::Scraper(format!(
@@ -205,8 +216,7 @@ impl LyricsScraper {
                 )));
             }
 
-            let data: serde_json::Value =
-                response.json().await.map_err(|e| TextGenError::Http(e))?;
+            let data: serde_json::Value = response.json().await.map_err(TextGenError::Http)?;
 
             let songs = data["response"]["songs"].as_array().ok_or_else(|| {
                 TextGenError::Scraper(format!(
@@ -225,11 +235,11 @@ impl LyricsScraper {
                 }
             }
 
-            if let Some(max_songs) = self.max_songs_per_artist {
-                if all_songs.len() >= max_songs {
-                    all_songs.truncate(max_songs);
-                    break;
-                }
+            if let Some(max_songs) = self.max_songs_per_artist
+                && all_songs.len() >= max_songs
+            {
+                all_songs.truncate(max_songs);
+                break;
             }
 
             page += 1;
@@ -239,12 +249,10 @@ impl LyricsScraper {
     }
 
     async fn get_song_lyrics(&self, song_url: &str) -> Result<String> {
-        let response = HTTP_CLIENT
-            .get(song_url)
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| TextGenError::Http(e))?;
+        let response = self
+            .requests
+            .send(HTTP_CLIENT.get(song_url).timeout(self.request_timeout))
+            .await?;
 
         if !response.status().is_success() {
             return Err(TextGenError::Scraper(format!(
@@ -254,7 +262,7 @@ impl LyricsScraper {
             )));
         }
 
-        let html = response.text().await.map_err(|e| TextGenError::Http(e))?;
+        let html = response.text().await.map_err(TextGenError::Http)?;
 
         let document = scraper::Html::parse_document(&html);
 
@@ -284,6 +292,10 @@ impl LyricsScraper {
 #[async_trait]
 impl Scraper for LyricsScraper {
     async fn fetch_text(&self) -> Result<String> {
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
         let mut all_lyrics = Vec::new();
 
         for artist_name in &self.artists {
@@ -316,7 +328,10 @@ impl Scraper for LyricsScraper {
                     match self.get_song_lyrics(&url_clone).await {
                         Ok(lyrics) => {
                             info!("Successfully fetched lyrics for '{}'", title_clone);
-                            Ok(format!("# {} - {}\n\n{}", artist_name, title_clone, lyrics))
+                            Ok(Document::new(lyrics)
+                                .with_title(title_clone)
+                                .with_author(artist_name)
+                                .with_source(url_clone))
                         }
                         Err(e) => {
                             warn!("Failed to get lyrics for '{}': {}", title_clone, e);
@@ -328,7 +343,7 @@ impl Scraper for LyricsScraper {
 
             let results = join_all(futures).await;
 
-            let artist_lyrics: Vec<String> = results
+            let artist_lyrics: Vec<Document> = results
                 .into_iter()
                 .filter_map(|result| result.ok())
                 .collect();
@@ -342,7 +357,7 @@ impl Scraper for LyricsScraper {
             ));
         }
 
-        Ok(all_lyrics.join("\n\n"))
+        Ok(all_lyrics)
     }
 }
 
@@ -353,6 +368,7 @@ impl Clone for LyricsScraper {
             artists: self.artists.clone(),
             max_songs_per_artist: self.max_songs_per_artist,
             request_timeout: self.request_timeout,
+            requests: self.requests.clone(),
         }
     }
 }
@@ -1,7 +1,11 @@
 pub mod basic;
+pub mod feed;
+pub mod gutenberg;
 pub mod lyrics;
 pub mod wikipedia;
 
 pub use basic::BasicScraper;
+pub use feed::{Feed, FeedEntry, FeedScraper};
+pub use gutenberg::{GutenbergScraper, GutenbergSource};
 pub use lyrics::LyricsScraper;
-pub use wikipedia::WikipediaScraper;
+pub use wikipedia::{WikipediaMode, WikipediaScraper};
@@ -1,20 +1,91 @@
 use crate::config::ScraperConfig;
 use crate::error::{Result, TextGenError};
-use crate::scrapers::HTTP_CLIENT;
 use crate::scrapers::scraper_trait::Scraper;
+use crate::scrapers::{Document, HTTP_CLIENT, RequestClient, RequestPolicy, join_documents};
 
 use async_trait::async_trait;
 use futures::future::join_all;
 use log::{info, warn};
-use serde_json::json;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::collections::{HashSet, VecDeque};
 use std::io::{self, BufRead};
+use std::sync::Arc;
 use std::time::Duration;
 
 const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
 
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// Cap on the topic list when categories or links are expanded
+const DEFAULT_MAX_PAGES: usize = 100;
+
+/// Namespaces of articles and categories in the MediaWiki API
+const ARTICLE_NAMESPACE: u64 = 0;
+const CATEGORY_NAMESPACE: u64 = 14;
+
+/// Sections that hold lists of links and citations rather than prose
+pub const DEFAULT_SKIPPED_SECTIONS: &[&str] = &[
+    "See also",
+    "References",
+    "Notes",
+    "Citations",
+    "Footnotes",
+    "Sources",
+    "Bibliography",
+    "Further reading",
+    "External links",
+];
+
+/// `== Heading ==` lines in plaintext extracts
+static SECTION_HEADING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(={2,6})\s*(.*?)\s*={2,6}\s*$").unwrap());
+
+/// How much of each article is fetched
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WikipediaMode {
+    /// The lead extract from the REST summary endpoint
+    #[default]
+    Summary,
+    /// The whole article as plain text, section by section
+    FullArticle,
+}
+
+impl WikipediaMode {
+    /// Parses "summary" or "full"
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "summary" => Ok(Self::Summary),
+            "full" | "article" | "full_article" => Ok(Self::FullArticle),
+            other => Err(TextGenError::Config(format!(
+                "Unknown Wikipedia mode '{}' (expected 'summary' or 'full')",
+                other
+            ))),
+        }
+    }
+}
+
 pub struct WikipediaScraper {
     topics: Vec<String>,
     request_timeout: Duration,
+    requests: Arc<RequestClient>,
+    mode: WikipediaMode,
+    skip_sections: Vec<String>,
+    language: String,
+    categories: Vec<String>,
+    category_depth: usize,
+    follow_links: bool,
+    max_pages: usize,
+}
+
+/// Titles from one page of a `list=categorymembers` or `prop=links` response
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TitleBatch {
+    pub articles: Vec<String>,
+    pub subcategories: Vec<String>,
+    /// Value to send back to fetch the next page, if any
+    pub continuation: Option<String>,
 }
 
 impl WikipediaScraper {
@@ -22,6 +93,17 @@ impl WikipediaScraper {
         Self {
             topics,
             request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            requests: Arc::new(RequestClient::default()),
+            mode: WikipediaMode::default(),
+            skip_sections: DEFAULT_SKIPPED_SECTIONS
+                .iter()
+                .map(|section| section.to_string())
+                .collect(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            categories: Vec::new(),
+            category_depth: 0,
+            follow_links: false,
+            max_pages: DEFAULT_MAX_PAGES,
         }
     }
 
@@ -30,7 +112,55 @@ impl WikipediaScraper {
         self
     }
 
-    pub fn interactive_config() -> Box<dyn Scraper> {
+    /// Sets concurrency, per-host rate limiting and retries for this scraper's requests
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.requests = Arc::new(RequestClient::new(policy));
+        self
+    }
+
+    pub fn with_mode(mut self, mode: WikipediaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Replaces the sections dropped from full articles (matched case-insensitively)
+    pub fn with_skip_sections(mut self, sections: Vec<String>) -> Self {
+        self.skip_sections = sections;
+        self
+    }
+
+    /// Uses another language edition, e.g. "de" for de.wikipedia.org
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// Adds every article in these categories ("Physics" or "Category:Physics")
+    pub fn with_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// How many levels of subcategories are crawled below each category
+    pub fn with_category_depth(mut self, depth: usize) -> Self {
+        self.category_depth = depth;
+        self
+    }
+
+    /// Adds the articles linked from each seed topic
+    pub fn with_follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Limits the number of articles fetched once topics are expanded
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Asks for the settings on stdin and saves them for next time
+    pub fn interactive_config() -> ScraperConfig {
         println!("-- Wikipedia Scraper Config --");
         println!("Enter topics to fetch (comma-separated):");
         let stdin = io::stdin();
@@ -44,6 +174,37 @@ impl WikipediaScraper {
             .filter(|s| !s.is_empty())
             .collect();
 
+        println!("Enter categories to crawl (comma-separated, optional):");
+        let mut categories_input = String::new();
+        stdin.lock().read_line(&mut categories_input).unwrap();
+
+        let categories: Vec<String> = categories_input
+            .trim()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        println!("Enter Wikipedia language code (default: en):");
+        let mut language_input = String::new();
+        stdin.lock().read_line(&mut language_input).unwrap();
+
+        let language = match language_input.trim() {
+            "" => DEFAULT_LANGUAGE,
+            language => language,
+        }
+        .to_lowercase();
+
+        println!("Fetch full articles instead of summaries? (y/N):");
+        let mut mode_input = String::new();
+        stdin.lock().read_line(&mut mode_input).unwrap();
+
+        let mode = if mode_input.trim().eq_ignore_ascii_case("y") {
+            WikipediaMode::FullArticle
+        } else {
+            WikipediaMode::Summary
+        };
+
         println!("Enter request timeout in seconds (default: 30):");
         let mut timeout_input = String::new();
         stdin.lock().read_line(&mut timeout_input).unwrap();
@@ -57,6 +218,9 @@ impl WikipediaScraper {
             scraper_type: "wikipedia".to_string(),
             settings: json!({
                 "topics": topics,
+                "categories": categories,
+                "language": language,
+                "mode": if mode == WikipediaMode::FullArticle { "full" } else { "summary" },
                 "request_timeout": request_timeout,
             }),
         };
@@ -65,10 +229,10 @@ impl WikipediaScraper {
             warn!("Failed to save config: {}", e);
         }
 
-        Box::new(WikipediaScraper::new(topics).with_request_timeout(request_timeout))
+        config
     }
 
-    pub fn from_config(settings: &serde_json::Value) -> Box<dyn Scraper> {
+    pub fn from_config(settings: &serde_json::Value) -> Result<Box<dyn Scraper>> {
         let topics = if let Some(topics_array) = settings["topics"].as_array() {
             topics_array
                 .iter()
@@ -82,58 +246,434 @@ impl WikipediaScraper {
             .as_u64()
             .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
 
-        Box::new(WikipediaScraper::new(topics).with_request_timeout(request_timeout))
+        let mode = settings["mode"]
+            .as_str()
+            .map(WikipediaMode::from_name)
+            .transpose()?
+            .unwrap_or_default();
+
+        let categories = settings["categories"]
+            .as_array()
+            .map(|categories| {
+                categories
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut scraper = WikipediaScraper::new(topics)
+            .with_mode(mode)
+            .with_language(settings["language"].as_str().unwrap_or(DEFAULT_LANGUAGE))
+            .with_categories(categories)
+            .with_category_depth(settings["category_depth"].as_u64().unwrap_or(0) as usize)
+            .with_follow_links(settings["follow_links"].as_bool().unwrap_or(false))
+            .with_max_pages(
+                settings["max_pages"]
+                    .as_u64()
+                    .map(|n| n as usize)
+                    .unwrap_or(DEFAULT_MAX_PAGES),
+            )
+            .with_request_timeout(request_timeout)
+            .with_request_policy(RequestPolicy::from_settings(settings));
+
+        if let Some(sections) = settings["skip_sections"].as_array() {
+            scraper = scraper.with_skip_sections(
+                sections
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect(),
+            );
+        }
+
+        Ok(Box::new(scraper))
     }
 
-    async fn fetch_wikipedia_summary(&self, topic: &str) -> Result<String> {
+    async fn fetch_wikipedia_summary(&self, topic: &str) -> Result<Document> {
         let url = format!(
-            "https://en.wikipedia.org/api/rest_v1/page/summary/{}",
+            "{}/api/rest_v1/page/summary/{}",
+            self.base_url()?,
             urlencoding::encode(topic)
         );
 
-        let response = HTTP_CLIENT
-            .get(&url)
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| TextGenError::Http(e))?;
+        let data = self.get_json(&url, topic).await?;
+        parse_summary(&data, topic, &self.language)
+    }
+
+    async fn fetch_wikipedia_article(&self, topic: &str) -> Result<Document> {
+        let url = format!(
+            "{}/w/api.php?action=query&format=json&formatversion=2\
+             &prop=extracts%7Cinfo&inprop=url&explaintext=1&exsectionformat=wiki\
+             &redirects=1&titles={}",
+            self.base_url()?,
+            urlencoding::encode(topic)
+        );
+
+        let data = self.get_json(&url, topic).await?;
+        parse_article(&data, topic, &self.language, &self.skip_sections)
+    }
+
+    fn base_url(&self) -> Result<String> {
+        let valid = !self.language.is_empty()
+            && self
+                .language
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c == '-');
+        if !valid {
+            return Err(TextGenError::Config(format!(
+                "Invalid Wikipedia language code '{}'",
+                self.language
+            )));
+        }
+        Ok(format!("https://{}.wikipedia.org", self.language))
+    }
+
+    /// Explicit topics followed by articles found in categories and links, without duplicates
+    async fn expand_topics(&self) -> Result<Vec<String>> {
+        let mut topics = TopicList::default();
+        for topic in &self.topics {
+            topics.insert(topic);
+        }
+
+        if self.categories.is_empty() && !self.follow_links {
+            return Ok(topics.titles);
+        }
+
+        let limit = self.max_pages.max(self.topics.len());
+
+        if self.follow_links {
+            for seed in &self.topics {
+                let mut continuation = None;
+                loop {
+                    if topics.len() >= limit {
+                        break;
+                    }
+                    let batch = self.fetch_links(seed, continuation.as_deref()).await?;
+                    for title in &batch.articles {
+                        if topics.len() >= limit {
+                            break;
+                        }
+                        topics.insert(title);
+                    }
+                    match batch.continuation {
+                        Some(next) => continuation = Some(next),
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        let mut queue: VecDeque<(String, usize)> = self
+            .categories
+            .iter()
+            .map(|category| (category_title(category), 0))
+            .collect();
+        let mut visited = HashSet::new();
+
+        while let Some((category, depth)) = queue.pop_front() {
+            if topics.len() >= limit {
+                break;
+            }
+            if !visited.insert(category.clone()) {
+                continue;
+            }
+            info!("Listing {} (depth {})", category, depth);
+
+            let mut continuation = None;
+            loop {
+                let batch = self
+                    .fetch_category_members(&category, continuation.as_deref())
+                    .await?;
+                for title in &batch.articles {
+                    if topics.len() >= limit {
+                        break;
+                    }
+                    topics.insert(title);
+                }
+                if depth < self.category_depth {
+                    queue.extend(
+                        batch
+                            .subcategories
+                            .into_iter()
+                            .map(|subcategory| (subcategory, depth + 1)),
+                    );
+                }
+                match batch.continuation {
+                    Some(next) if topics.len() < limit => continuation = Some(next),
+                    _ => break,
+                }
+            }
+        }
+
+        info!("Expanded to {} Wikipedia articles", topics.len());
+        Ok(topics.titles)
+    }
+
+    async fn fetch_category_members(
+        &self,
+        category: &str,
+        continuation: Option<&str>,
+    ) -> Result<TitleBatch> {
+        let mut url = format!(
+            "{}/w/api.php?action=query&format=json&formatversion=2&list=categorymembers\
+             &cmtype=page%7Csubcat&cmlimit=500&cmtitle={}",
+            self.base_url()?,
+            urlencoding::encode(category)
+        );
+        if let Some(next) = continuation {
+            url.push_str(&format!("&cmcontinue={}", urlencoding::encode(next)));
+        }
+
+        let data = self.get_json(&url, category).await?;
+        Ok(parse_category_members(&data))
+    }
+
+    async fn fetch_links(&self, topic: &str, continuation: Option<&str>) -> Result<TitleBatch> {
+        let mut url = format!(
+            "{}/w/api.php?action=query&format=json&formatversion=2&prop=links\
+             &plnamespace=0&pllimit=max&redirects=1&titles={}",
+            self.base_url()?,
+            urlencoding::encode(topic)
+        );
+        if let Some(next) = continuation {
+            url.push_str(&format!("&plcontinue={}", urlencoding::encode(next)));
+        }
+
+        let data = self.get_json(&url, topic).await?;
+        Ok(parse_links(&data))
+    }
+
+    async fn get_json(&self, url: &str, topic: &str) -> Result<Value> {
+        let response = self
+            .requests
+            .send(HTTP_CLIENT.get(url).timeout(self.request_timeout))
+            .await?;
 
         if !response.status().is_success() {
             return Err(TextGenError::Scraper(format!(
-                "Failed to fetch Wikipedia summary for '{}': HTTP {}",
+                "Failed to fetch Wikipedia page for '{}': HTTP {}",
                 topic,
                 response.status()
             )));
         }
 
-        let data: serde_json::Value = response.json().await.map_err(|e| TextGenError::Http(e))?;
+        response.json().await.map_err(TextGenError::Http)
+    }
+}
+
+/// Builds a document from a REST `page/summary` response
+///
+/// The page's language comes from the response, or `language` when it has none.
+pub fn parse_summary(data: &Value, topic: &str, language: &str) -> Result<Document> {
+    let title = data["title"].as_str().unwrap_or(topic);
+    let extract = data["extract"]
+        .as_str()
+        .ok_or_else(|| TextGenError::Scraper(format!("No extract found for topic '{}'", topic)))?;
+
+    let language = data["lang"].as_str().unwrap_or(language);
+    let mut document = Document::new(extract)
+        .with_title(title)
+        .with_language(language);
+    if let Some(page_url) = data["content_urls"]["desktop"]["page"].as_str() {
+        document = document.with_source(page_url);
+    }
+    Ok(document)
+}
+
+/// Builds a document from an `action=query&prop=extracts` response (formatversion=2)
+///
+/// Section headings become `##`/`###` lines and sections named in `skip_sections`
+/// are dropped together with their subsections. The page's language comes from
+/// the response, or `language` when it has none.
+pub fn parse_article(
+    data: &Value,
+    topic: &str,
+    language: &str,
+    skip_sections: &[String],
+) -> Result<Document> {
+    let page = data["query"]["pages"]
+        .as_array()
+        .and_then(|pages| pages.first())
+        .ok_or_else(|| TextGenError::Scraper(format!("No page returned for topic '{}'", topic)))?;
+
+    if page["missing"].as_bool().unwrap_or(false) || page.get("invalid").is_some() {
+        return Err(TextGenError::Scraper(format!(
+            "Wikipedia has no article for '{}'",
+            topic
+        )));
+    }
+
+    let title = page["title"].as_str().unwrap_or(topic);
+    let extract = page["extract"]
+        .as_str()
+        .ok_or_else(|| TextGenError::Scraper(format!("No extract found for topic '{}'", topic)))?;
+
+    let text = format_sections(extract, skip_sections);
+    if text.is_empty() {
+        return Err(TextGenError::Scraper(format!(
+            "Article '{}' has no text",
+            title
+        )));
+    }
+
+    let language = page["pagelanguage"].as_str().unwrap_or(language);
+    let mut document = Document::new(text)
+        .with_title(title)
+        .with_language(language);
+    if let Some(page_url) = page["fullurl"].as_str() {
+        document = document.with_source(page_url);
+    }
+    Ok(document)
+}
+
+/// Reads article and subcategory titles from a `list=categorymembers` response
+pub fn parse_category_members(data: &Value) -> TitleBatch {
+    let mut batch = TitleBatch {
+        continuation: data["continue"]["cmcontinue"].as_str().map(str::to_string),
+        ..TitleBatch::default()
+    };
+
+    for member in data["query"]["categorymembers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let Some(title) = member["title"].as_str() else {
+            continue;
+        };
+        match member["ns"].as_u64() {
+            Some(ARTICLE_NAMESPACE) => batch.articles.push(title.to_string()),
+            Some(CATEGORY_NAMESPACE) => batch.subcategories.push(title.to_string()),
+            _ => {}
+        }
+    }
+
+    batch
+}
+
+/// Reads linked article titles from a `prop=links` response
+pub fn parse_links(data: &Value) -> TitleBatch {
+    let articles = data["query"]["pages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|page| page["links"].as_array().into_iter().flatten())
+        .filter(|link| link["ns"].as_u64() == Some(ARTICLE_NAMESPACE))
+        .filter_map(|link| link["title"].as_str().map(str::to_string))
+        .collect();
 
-        let title = data["title"].as_str().unwrap_or(topic);
-        let extract = data["extract"].as_str().ok_or_else(|| {
-            TextGenError::Scraper(format!("No extract found for topic '{}'", topic))
-        })?;
+    TitleBatch {
+        articles,
+        subcategories: Vec::new(),
+        continuation: data["continue"]["plcontinue"].as_str().map(str::to_string),
+    }
+}
 
-        Ok(format!("# {}\n\n{}", title, extract))
+/// Adds the canonical namespace prefix, which every language edition accepts
+fn category_title(category: &str) -> String {
+    if category.contains(':') {
+        category.to_string()
+    } else {
+        format!("Category:{}", category)
     }
 }
 
+/// Topic titles in insertion order, ignoring repeats that differ only in `_` vs space
+#[derive(Default)]
+struct TopicList {
+    titles: Vec<String>,
+    seen: HashSet<String>,
+}
+
+impl TopicList {
+    fn insert(&mut self, title: &str) {
+        if self.seen.insert(title.replace('_', " ")) {
+            self.titles.push(title.to_string());
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.titles.len()
+    }
+}
+
+/// Converts a plaintext extract into paragraphs and `#` headings
+fn format_sections(extract: &str, skip_sections: &[String]) -> String {
+    // (heading level, text); level 0 is a paragraph
+    let mut blocks: Vec<(usize, String)> = Vec::new();
+    let mut skipping_below: Option<usize> = None;
+
+    for line in extract
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        if let Some(caps) = SECTION_HEADING.captures(line) {
+            let level = caps[1].len();
+            let heading = caps[2].trim();
+
+            if skipping_below.is_some_and(|skipped| level > skipped) {
+                continue;
+            }
+            skipping_below = skip_sections
+                .iter()
+                .any(|section| section.eq_ignore_ascii_case(heading))
+                .then_some(level);
+            if skipping_below.is_none() {
+                blocks.push((level, heading.to_string()));
+            }
+        } else if skipping_below.is_none() {
+            blocks.push((0, line.to_string()));
+        }
+    }
+
+    // Headings of sections left empty (e.g. after skipping) would only add noise
+    let mut kept: Vec<String> = Vec::new();
+    for (i, (level, text)) in blocks.iter().enumerate() {
+        if *level == 0 {
+            kept.push(text.clone());
+            continue;
+        }
+        let has_content = blocks[i + 1..]
+            .iter()
+            .take_while(|(next, _)| *next == 0 || next > level)
+            .any(|(next, _)| *next == 0);
+        if has_content {
+            kept.push(format!("{} {}", "#".repeat(*level), text));
+        }
+    }
+
+    kept.join("\n\n")
+}
+
 #[async_trait]
 impl Scraper for WikipediaScraper {
     async fn fetch_text(&self) -> Result<String> {
-        if self.topics.is_empty() {
-            return Err(TextGenError::Scraper("No topics specified".to_string()));
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        if self.topics.is_empty() && self.categories.is_empty() {
+            return Err(TextGenError::Scraper(
+                "No topics or categories specified".to_string(),
+            ));
         }
 
-        let futures = self.topics.iter().map(|topic| {
+        let topics = self.expand_topics().await?;
+        let futures = topics.iter().map(|topic| {
             let topic_clone = topic.clone();
             async move {
-                match self.fetch_wikipedia_summary(&topic_clone).await {
-                    Ok(summary) => {
-                        info!("Successfully fetched summary for '{}'", topic_clone);
-                        Ok(summary)
+                let result = match self.mode {
+                    WikipediaMode::Summary => self.fetch_wikipedia_summary(&topic_clone).await,
+                    WikipediaMode::FullArticle => self.fetch_wikipedia_article(&topic_clone).await,
+                };
+                match result {
+                    Ok(document) => {
+                        info!("Successfully fetched article for '{}'", topic_clone);
+                        Ok(document)
                     }
                     Err(e) => {
-                        warn!("Failed to get summary for '{}': {}", topic_clone, e);
+                        warn!("Failed to get article for '{}': {}", topic_clone, e);
                         Err(e)
                     }
                 }
@@ -142,18 +682,18 @@ impl Scraper for WikipediaScraper {
 
         let results = join_all(futures).await;
 
-        let summaries: Vec<String> = results
+        let summaries: Vec<Document> = results
             .into_iter()
             .filter_map(|result| result.ok())
             .collect();
 
         if summaries.is_empty() {
             return Err(TextGenError::Scraper(
-                "No Wikipedia summaries were successfully fetched".to_string(),
+                "No Wikipedia articles were successfully fetched".to_string(),
             ));
         }
 
-        Ok(summaries.join("\n\n"))
+        Ok(summaries)
     }
 }
 
@@ -162,6 +702,14 @@ impl Clone for WikipediaScraper {
         Self {
             topics: self.topics.clone(),
             request_timeout: self.request_timeout,
+            requests: self.requests.clone(),
+            mode: self.mode,
+            skip_sections: self.skip_sections.clone(),
+            language: self.language.clone(),
+            categories: self.categories.clone(),
+            category_depth: self.category_depth,
+            follow_links: self.follow_links,
+            max_pages: self.max_pages,
         }
     }
 }
@@ -4,13 +4,13 @@ use std::collections::HashMap;
 use crate::config::ScraperConfig;
 use crate::error::{Result, TextGenError};
 use crate::scrapers::{
-    providers::{BasicScraper, LyricsScraper, WikipediaScraper},
+    providers::{BasicScraper, FeedScraper, GutenbergScraper, LyricsScraper, WikipediaScraper},
     scraper_trait::Scraper,
 };
 
 pub struct ScraperFactory {
-    pub interactive: Option<fn() -> Box<dyn Scraper>>,
-    pub from_config: fn(settings: &serde_json::Value) -> Box<dyn Scraper>,
+    pub interactive: Option<fn() -> ScraperConfig>,
+    pub from_config: fn(settings: &serde_json::Value) -> Result<Box<dyn Scraper>>,
 }
 
 pub static SCRAPER_REGISTRY: Lazy<HashMap<&'static str, ScraperFactory>> = Lazy::new(|| {
@@ -40,12 +40,28 @@ pub static SCRAPER_REGISTRY: Lazy<HashMap<&'static str, ScraperFactory>> = Lazy:
         },
     );
 
+    m.insert(
+        "feed",
+        ScraperFactory {
+            interactive: Some(FeedScraper::interactive_config),
+            from_config: FeedScraper::from_config,
+        },
+    );
+
+    m.insert(
+        "gutenberg",
+        ScraperFactory {
+            interactive: Some(GutenbergScraper::interactive_config),
+            from_config: GutenbergScraper::from_config,
+        },
+    );
+
     m
 });
 
 pub fn load_scraper_from_config(config: &ScraperConfig) -> Result<Box<dyn Scraper>> {
     if let Some(factory) = SCRAPER_REGISTRY.get(config.scraper_type.as_str()) {
-        Ok((factory.from_config)(&config.settings))
+        (factory.from_config)(&config.settings)
     } else {
         Err(TextGenError::Config(format!(
             "Unknown scraper type '{}'",
@@ -54,7 +70,8 @@ pub fn load_scraper_from_config(config: &ScraperConfig) -> Result<Box<dyn Scrape
     }
 }
 
-pub fn get_scraper_interactive(scraper_type: &str) -> Result<Box<dyn Scraper>> {
+/// Asks for the settings of `scraper_type` on stdin
+pub fn configure_interactive(scraper_type: &str) -> Result<ScraperConfig> {
     if let Some(factory) = SCRAPER_REGISTRY.get(scraper_type) {
         if let Some(interactive_fn) = factory.interactive {
             Ok(interactive_fn())
@@ -71,3 +88,7 @@ pub fn get_scraper_interactive(scraper_type: &str) -> Result<Box<dyn Scraper>> {
         )))
     }
 }
+
+pub fn get_scraper_interactive(scraper_type: &str) -> Result<Box<dyn Scraper>> {
+    load_scraper_from_config(&configure_interactive(scraper_type)?)
+}
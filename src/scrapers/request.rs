@@ -0,0 +1,537 @@
+use super::cache::{self, ResponseCache};
+use super::http::{HTTP_CLIENT, USER_AGENT};
+use super::robots::RobotsRules;
+use crate::error::{Result, TextGenError};
+use log::{debug, warn};
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::time::{Instant, sleep, sleep_until};
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BUDGET: usize = 50;
+const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 500;
+const DEFAULT_MAX_RETRY_DELAY_SECS: u64 = 60;
+
+/// Limits and retry behaviour for a scraper's HTTP requests
+///
+/// Read from `ScraperConfig.settings` with [`RequestPolicy::from_settings`]:
+/// `max_concurrent_requests`, `requests_per_second` (per host), `max_retries`,
+/// `retry_budget`, `retry_base_delay_ms`, `max_retry_delay_secs` and
+/// `ignore_robots`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestPolicy {
+    /// Requests in flight at once
+    pub max_concurrent: usize,
+    /// Minimum time between two requests to the same host
+    pub min_interval: Duration,
+    /// Retries of a single request
+    pub max_retries: u32,
+    /// Retries shared by every request made through one client
+    pub retry_budget: usize,
+    /// Delay before the first retry; doubled for each further attempt
+    pub base_delay: Duration,
+    /// Longest wait before a retry; a longer `Retry-After` gives up on the request
+    pub max_delay: Duration,
+    /// Skip robots.txt rules and crawl delays, for sites the user may crawl freely
+    pub ignore_robots: bool,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            max_concurrent: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            min_interval: Duration::ZERO,
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_budget: DEFAULT_RETRY_BUDGET,
+            base_delay: Duration::from_millis(DEFAULT_RETRY_BASE_DELAY_MS),
+            max_delay: Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS),
+            ignore_robots: false,
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Reads the policy from scraper settings, using defaults for missing keys
+    pub fn from_settings(settings: &serde_json::Value) -> Self {
+        let defaults = Self::default();
+
+        let min_interval = settings["requests_per_second"]
+            .as_f64()
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| Duration::from_secs_f64(1.0 / rate))
+            .unwrap_or(defaults.min_interval);
+
+        Self {
+            max_concurrent: settings["max_concurrent_requests"]
+                .as_u64()
+                .map(|n| (n as usize).max(1))
+                .unwrap_or(defaults.max_concurrent),
+            min_interval,
+            max_retries: settings["max_retries"]
+                .as_u64()
+                .map(|n| n as u32)
+                .unwrap_or(defaults.max_retries),
+            retry_budget: settings["retry_budget"]
+                .as_u64()
+                .map(|n| n as usize)
+                .unwrap_or(defaults.retry_budget),
+            base_delay: settings["retry_base_delay_ms"]
+                .as_u64()
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            max_delay: settings["max_retry_delay_secs"]
+                .as_u64()
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_delay),
+            ignore_robots: settings["ignore_robots"]
+                .as_bool()
+                .unwrap_or(defaults.ignore_robots),
+        }
+    }
+
+    /// Exponential backoff before retry number `attempt` (0-based), without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+/// Sends requests under a [`RequestPolicy`]
+///
+/// Failed requests (timeouts, connection errors, 429 and 5xx responses) are
+/// retried with jittered exponential backoff, honouring `Retry-After`, until
+/// the per-request limit or the shared retry budget runs out. A `Retry-After`
+/// beyond the policy's `max_delay` ends the retries instead. GET requests
+/// go through the installed [`ResponseCache`], if any. Clients built
+/// [`with_robots`](Self::with_robots) also obey each site's robots.txt.
+pub struct RequestClient {
+    policy: RequestPolicy,
+    cache: Option<Arc<ResponseCache>>,
+    permits: Semaphore,
+    /// Earliest time the next request may go to each host
+    next_slot: Mutex<HashMap<String, Instant>>,
+    retries_left: AtomicUsize,
+    respect_robots: bool,
+    /// Parsed robots.txt per origin, filled in once it has been fetched
+    robots: Mutex<HashMap<String, Arc<OnceCell<Arc<RobotsRules>>>>>,
+    /// Crawl-delay of each host, when longer than the policy's interval
+    crawl_delays: Mutex<HashMap<String, Duration>>,
+}
+
+impl Default for RequestClient {
+    fn default() -> Self {
+        Self::new(RequestPolicy::default())
+    }
+}
+
+impl RequestClient {
+    pub fn new(policy: RequestPolicy) -> Self {
+        Self {
+            permits: Semaphore::new(policy.max_concurrent.max(1)),
+            next_slot: Mutex::new(HashMap::new()),
+            retries_left: AtomicUsize::new(policy.retry_budget),
+            cache: cache::shared(),
+            respect_robots: false,
+            robots: Mutex::new(HashMap::new()),
+            crawl_delays: Mutex::new(HashMap::new()),
+            policy,
+        }
+    }
+
+    /// Checks robots.txt before each request and honours its Crawl-delay
+    ///
+    /// Disallowed URLs fail with a scraper error. A policy with
+    /// `ignore_robots` set overrides this, with a warning.
+    pub fn with_robots(mut self, respect_robots: bool) -> Self {
+        if respect_robots && self.policy.ignore_robots {
+            warn!("Ignoring robots.txt rules and crawl delays as requested");
+        }
+        self.respect_robots = respect_robots && !self.policy.ignore_robots;
+        self
+    }
+
+    /// Uses `cache` instead of the installed one (`None` disables caching)
+    pub fn with_cache(mut self, cache: Option<Arc<ResponseCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn policy(&self) -> &RequestPolicy {
+        &self.policy
+    }
+
+    /// Retries still available to requests made through this client
+    pub fn retries_left(&self) -> usize {
+        self.retries_left.load(Ordering::Relaxed)
+    }
+
+    /// Sends `request`, answering from the cache when possible and retrying transient failures
+    ///
+    /// The last response is returned once retries are exhausted, so callers
+    /// still see (and report) the final HTTP status.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        if self.respect_robots {
+            let probe = request
+                .try_clone()
+                .ok_or_else(|| TextGenError::Unknown("Request cannot be checked".to_string()))?
+                .build()
+                .map_err(TextGenError::Http)?;
+            self.check_robots(probe.url()).await?;
+        }
+
+        self.send_cached(request).await
+    }
+
+    async fn send_cached(&self, request: RequestBuilder) -> Result<Response> {
+        let Some(cache) = self.cache.as_deref() else {
+            return self.send_with_retries(request).await;
+        };
+
+        let probe = request
+            .try_clone()
+            .ok_or_else(|| TextGenError::Unknown("Request cannot be cached".to_string()))?
+            .build()
+            .map_err(TextGenError::Http)?;
+        if probe.method() != Method::GET {
+            return self.send_with_retries(request).await;
+        }
+
+        let url = probe.url().to_string();
+        let key = ResponseCache::key(&probe);
+        let cached = cache.get(&key, &url);
+
+        let request = match &cached {
+            Some(entry) if cache.is_offline() || entry.is_fresh(cache.ttl()) => {
+                debug!("Serving {} from cache", url);
+                return entry.to_response();
+            }
+            None if cache.is_offline() => {
+                return Err(TextGenError::Scraper(format!(
+                    "{} is not in the HTTP cache and offline mode is on",
+                    url
+                )));
+            }
+            Some(entry) => entry.add_validators(request),
+            None => request,
+        };
+
+        let response = self.send_with_retries(request).await?;
+        match cached {
+            Some(entry) if response.status() == StatusCode::NOT_MODIFIED => {
+                debug!("{} not modified, refreshing cache entry", url);
+                cache.touch(&key, &entry);
+                entry.to_response()
+            }
+            _ if response.status().is_success() => cache.store(&key, &url, response).await,
+            _ => Ok(response),
+        }
+    }
+
+    async fn send_with_retries(&self, request: RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+
+        loop {
+            let (client, request) = request
+                .try_clone()
+                .ok_or_else(|| TextGenError::Unknown("Request cannot be retried".to_string()))?
+                .build_split();
+            let request = request.map_err(TextGenError::Http)?;
+            let url = request.url().clone();
+            let host = url.host_str().unwrap_or_default().to_string();
+
+            // A request waiting for its host's slot does not hold up other hosts
+            self.wait_for_host(&host).await;
+            let outcome = {
+                let _permit = self
+                    .permits
+                    .acquire()
+                    .await
+                    .map_err(|e| TextGenError::Unknown(e.to_string()))?;
+                client.execute(request).await
+            };
+
+            let delay = match &outcome {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    match retry_after(response) {
+                        Some(delay) if delay > self.policy.max_delay => {
+                            warn!(
+                                "{} asked to retry after {:.0}s, more than the {:.0}s limit; giving up",
+                                url,
+                                delay.as_secs_f64(),
+                                self.policy.max_delay.as_secs_f64()
+                            );
+                            return outcome.map_err(TextGenError::Http);
+                        }
+                        Some(delay) => {
+                            // The server asked every client to slow down, not just this request
+                            self.defer_host(&host, delay);
+                            delay
+                        }
+                        None => self.jittered_backoff(attempt),
+                    }
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => self.jittered_backoff(attempt),
+                _ => return outcome.map_err(TextGenError::Http),
+            };
+
+            if attempt >= self.policy.max_retries || !self.take_retry() {
+                debug!("Giving up on {} after {} retries", url, attempt);
+                return outcome.map_err(TextGenError::Http);
+            }
+
+            match &outcome {
+                Ok(response) => warn!(
+                    "{} returned HTTP {}, retrying in {:.1}s",
+                    url,
+                    response.status(),
+                    delay.as_secs_f64()
+                ),
+                Err(e) => warn!(
+                    "{} failed ({}), retrying in {:.1}s",
+                    url,
+                    e,
+                    delay.as_secs_f64()
+                ),
+            }
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Fails if robots.txt disallows `url`, fetching the site's rules on first use
+    ///
+    /// Only requests to the same origin wait while its robots.txt is fetched.
+    /// When it cannot be fetched, `url` is refused and the next request to
+    /// the origin tries again.
+    async fn check_robots(&self, url: &Url) -> Result<()> {
+        let origin = url.origin().ascii_serialization();
+        let cell = self
+            .robots
+            .lock()
+            .unwrap()
+            .entry(origin.clone())
+            .or_default()
+            .clone();
+
+        let rules = cell
+            .get_or_try_init(|| async {
+                let rules = self.fetch_robots(&origin).await?;
+                if let Some(delay) = rules.crawl_delay()
+                    && delay > self.policy.min_interval
+                {
+                    debug!(
+                        "Using crawl delay of {:.1}s for {}",
+                        delay.as_secs_f64(),
+                        origin
+                    );
+                    self.crawl_delays.lock().unwrap().insert(
+                        url.host_str().unwrap_or_default().to_string(),
+                        delay.min(self.policy.max_delay),
+                    );
+                }
+                Ok::<_, TextGenError>(Arc::new(rules))
+            })
+            .await
+            .map_err(|e| TextGenError::Scraper(format!("Skipping {}: {}", url, e)))?;
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if rules.is_allowed(&path) {
+            Ok(())
+        } else {
+            Err(TextGenError::Scraper(format!(
+                "{} is disallowed by robots.txt",
+                url
+            )))
+        }
+    }
+
+    /// A missing robots.txt (4xx) allows everything; an unreachable one is an error
+    ///
+    /// In offline mode a robots.txt that was never cached cannot be checked.
+    /// The site is then crawled from the cache without rules, with a warning,
+    /// since every page served offline was already fetched once.
+    async fn fetch_robots(&self, origin: &str) -> Result<RobotsRules> {
+        let url = format!("{}/robots.txt", origin);
+        let unavailable = |reason: String| {
+            warn!("Could not fetch {}: {}", url, reason);
+            TextGenError::Scraper(format!("{} is unavailable ({})", url, reason))
+        };
+
+        let response = match self.send_cached(HTTP_CLIENT.get(&url)).await {
+            Ok(response) => response,
+            // Only raised for an offline cache miss
+            Err(TextGenError::Scraper(e)) => {
+                warn!("Not checking robots.txt for {}: {}", origin, e);
+                return Ok(RobotsRules::allow_all());
+            }
+            Err(e) => return Err(unavailable(e.to_string())),
+        };
+
+        let status = response.status();
+        if status.is_client_error() {
+            debug!("{} returned HTTP {}, crawling without rules", url, status);
+            return Ok(RobotsRules::allow_all());
+        }
+        if !status.is_success() {
+            return Err(unavailable(format!("HTTP {}", status)));
+        }
+
+        match response.text().await {
+            Ok(text) => Ok(RobotsRules::parse(&text, USER_AGENT)),
+            Err(e) => Err(unavailable(e.to_string())),
+        }
+    }
+
+    async fn wait_for_host(&self, host: &str) {
+        let interval = self.host_interval(host);
+        if interval.is_zero() && !self.is_deferred(host) {
+            return;
+        }
+
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slot.get(host).copied().unwrap_or(now).max(now);
+            next_slot.insert(host.to_string(), slot + interval);
+            slot
+        };
+        sleep_until(slot).await;
+    }
+
+    /// Minimum time between requests to `host`: the policy's or the site's Crawl-delay
+    fn host_interval(&self, host: &str) -> Duration {
+        self.crawl_delays
+            .lock()
+            .unwrap()
+            .get(host)
+            .copied()
+            .unwrap_or(self.policy.min_interval)
+    }
+
+    fn is_deferred(&self, host: &str) -> bool {
+        self.next_slot
+            .lock()
+            .unwrap()
+            .get(host)
+            .is_some_and(|slot| *slot > Instant::now())
+    }
+
+    fn defer_host(&self, host: &str, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.entry(host.to_string()).or_insert(until);
+        *slot = (*slot).max(until);
+    }
+
+    fn take_retry(&self) -> bool {
+        self.retries_left
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Backoff scaled by a random factor in [0.5, 1] so clients don't retry in lockstep
+    fn jittered_backoff(&self, attempt: u32) -> Duration {
+        self.policy
+            .backoff(attempt)
+            .mul_f64(rand::rng().random_range(0.5..=1.0))
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || matches!(status.as_u16(), 500 | 502 | 503 | 504)
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    parse_retry_after(response.headers().get(RETRY_AFTER)?.to_str().ok()?)
+}
+
+/// Parses a `Retry-After` value: delay in seconds or an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_policy_from_settings() {
+        let policy = RequestPolicy::from_settings(&json!({
+            "max_concurrent_requests": 2,
+            "requests_per_second": 4.0,
+            "max_retries": 5,
+            "retry_budget": 10,
+            "max_retry_delay_secs": 5,
+            "ignore_robots": true,
+        }));
+
+        assert_eq!(policy.max_concurrent, 2);
+        assert_eq!(policy.min_interval, Duration::from_millis(250));
+        assert_eq!(policy.max_retries, 5);
+        assert_eq!(policy.retry_budget, 10);
+        assert_eq!(policy.base_delay, RequestPolicy::default().base_delay);
+        assert_eq!(policy.max_delay, Duration::from_secs(5));
+        assert!(policy.ignore_robots);
+        assert!(!RequestPolicy::default().ignore_robots);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RequestPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            ..RequestPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(40), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_retry_budget_is_shared() {
+        let client = RequestClient::new(RequestPolicy {
+            retry_budget: 2,
+            ..RequestPolicy::default()
+        });
+
+        assert!(client.take_retry());
+        assert!(client.take_retry());
+        assert!(!client.take_retry());
+        assert_eq!(client.retries_left(), 0);
+    }
+}
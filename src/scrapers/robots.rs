@@ -0,0 +1,205 @@
+use regex::Regex;
+use std::time::Duration;
+
+/// The rules of a robots.txt file that apply to one user agent
+#[derive(Clone, Debug, Default)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    allow: bool,
+    /// Length of the original path pattern; the longest matching rule wins
+    specificity: usize,
+    pattern: Regex,
+}
+
+impl RobotsRules {
+    /// Rules that allow everything, used when a site has no robots.txt
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Parses robots.txt, keeping the groups for `user_agent` ("name/version")
+    ///
+    /// Groups naming the agent's product token (compared case-insensitively)
+    /// take precedence over `*`; several matching groups are merged.
+    pub fn parse(robots_txt: &str, user_agent: &str) -> Self {
+        let token = user_agent
+            .split('/')
+            .next()
+            .unwrap_or(user_agent)
+            .trim()
+            .to_lowercase();
+
+        let mut specific = Self::default();
+        let mut wildcard = Self::default();
+        let mut found_specific = false;
+
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in robots_txt.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                if !value.is_empty() {
+                    agents.push(value.to_lowercase());
+                }
+                continue;
+            }
+            in_rules = true;
+
+            let is_specific = agents.contains(&token);
+            let is_wildcard = agents.iter().any(|agent| agent == "*");
+            let target = if is_specific {
+                found_specific = true;
+                &mut specific
+            } else if is_wildcard {
+                &mut wildcard
+            } else {
+                continue;
+            };
+
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => {
+                    target.rules.push(Rule::new(key == "allow", value));
+                }
+                "crawl-delay" => {
+                    if let Ok(seconds) = value.parse::<f64>()
+                        && seconds.is_finite()
+                        && seconds >= 0.0
+                    {
+                        target.crawl_delay = Some(Duration::from_secs_f64(seconds));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if found_specific { specific } else { wildcard }
+    }
+
+    /// Checks a URL path (with query string) against the rules
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| rule.pattern.is_match(path))
+            .max_by_key(|rule| (rule.specificity, rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// Minimum delay between requests asked for by the site
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+impl Rule {
+    /// Compiles a path pattern: `*` matches anything and a trailing `$` anchors the end
+    fn new(allow: bool, path: &str) -> Self {
+        let (path, anchored) = match path.strip_suffix('$') {
+            Some(path) => (path, true),
+            None => (path, false),
+        };
+        let body = path
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*");
+        let pattern = format!("^{}{}", body, if anchored { "$" } else { "" });
+
+        Self {
+            allow,
+            specificity: path.len(),
+            pattern: Regex::new(&pattern).expect("escaped robots.txt pattern is valid"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+# Example robots.txt
+User-agent: Googlebot
+Disallow: /
+
+User-agent: text-gen-ngram
+User-agent: other-bot
+Disallow: /private/
+Allow: /private/public-*.html$
+Crawl-delay: 2.5
+
+User-agent: *
+Disallow: /
+";
+
+    #[test]
+    fn test_specific_group_wins() {
+        let rules = RobotsRules::parse(ROBOTS, "text-gen-ngram/1.0");
+
+        assert!(rules.is_allowed("/"));
+        assert!(rules.is_allowed("/blog/post?id=1"));
+        assert!(!rules.is_allowed("/private/notes.html"));
+        assert!(rules.is_allowed("/private/public-page.html"));
+        assert!(!rules.is_allowed("/private/public-page.html?x=1"));
+        assert_eq!(rules.crawl_delay(), Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn test_wildcard_group_applies_to_others() {
+        let rules = RobotsRules::parse(ROBOTS, "some-crawler/2.0");
+
+        assert!(!rules.is_allowed("/"));
+        assert_eq!(rules.crawl_delay(), None);
+    }
+
+    #[test]
+    fn test_agent_must_match_product_token() {
+        // Neither a prefix of our token nor an empty agent names us
+        let rules = RobotsRules::parse(
+            "User-agent: text\nDisallow: /\n\nUser-agent:\nDisallow: /\n\n\
+             User-agent: *\nDisallow: /tmp/\n",
+            "text-gen-ngram/1.0",
+        );
+        assert!(rules.is_allowed("/"));
+        assert!(!rules.is_allowed("/tmp/x"));
+
+        let rules = RobotsRules::parse(
+            "User-agent: Text-Gen-NGram\nDisallow: /\n",
+            "text-gen-ngram/1.0",
+        );
+        assert!(!rules.is_allowed("/"));
+    }
+
+    #[test]
+    fn test_longest_match_wins() {
+        let rules = RobotsRules::parse(
+            "User-agent: *\nDisallow: /docs\nAllow: /docs/\nDisallow: /docs/old*\nAllow: /docs/old",
+            "text-gen-ngram/1.0",
+        );
+
+        assert!(!rules.is_allowed("/docs"));
+        assert!(rules.is_allowed("/docs/guide"));
+        assert!(!rules.is_allowed("/docs/old-api"));
+    }
+
+    #[test]
+    fn test_empty_and_fallback_rules() {
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:\n", "a/1").is_allowed("/x"));
+        assert!(RobotsRules::allow_all().is_allowed("/anything"));
+    }
+}
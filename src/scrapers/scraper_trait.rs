@@ -1,7 +1,6 @@
 use crate::error::Result;
+use crate::scrapers::Document;
 use async_trait::async_trait;
-use std::time::Duration;
-use tokio::time::sleep;
 
 #[async_trait]
 pub trait Scraper: Send + Sync {
@@ -18,17 +17,13 @@ pub trait Scraper: Send + Sync {
     /// A Result containing either the scraped text or an error
     async fn fetch_text(&self) -> Result<String>;
 
-    /// Applies rate limiting by sleeping for the specified duration
+    /// Fetches the source as separate documents with their metadata
     ///
-    /// This is a utility method that can be used by implementations to
-    /// respect rate limits of the services they're scraping.
-    ///
-    /// # Arguments
-    ///
-    /// * `duration` - The duration to sleep for
-    async fn rate_limit(&self, duration: Duration) -> Result<()> {
-        sleep(duration).await;
-        Ok(())
+    /// Scrapers that collect several items (songs, articles, pages) should
+    /// override this so that training, deduplication and statistics can keep
+    /// them apart. The default wraps [`Scraper::fetch_text`] in one document.
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        Ok(vec![Document::new(self.fetch_text().await?)])
     }
 }
 
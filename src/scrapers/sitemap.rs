@@ -0,0 +1,163 @@
+use crate::error::{Result, TextGenError};
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+/// Largest sitemap the protocol allows, uncompressed
+pub const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+/// One `<url>` or `<sitemap>` entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<DateTime<Utc>>,
+}
+
+/// A parsed sitemap: either page URLs or, for a sitemap index, further sitemaps
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sitemap {
+    Urls(Vec<SitemapEntry>),
+    Index(Vec<SitemapEntry>),
+}
+
+impl SitemapEntry {
+    /// Whether the entry changed on or after `since`; entries without a date always pass
+    pub fn modified_since(&self, since: DateTime<Utc>) -> bool {
+        self.lastmod.is_none_or(|lastmod| lastmod >= since)
+    }
+}
+
+/// Parses a `<urlset>` or `<sitemapindex>` document, gzipped or not
+pub fn parse_sitemap(bytes: &[u8]) -> Result<Sitemap> {
+    let mut decompressed = String::new();
+    let xml = if bytes.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(bytes)
+            .take(MAX_SITEMAP_BYTES as u64)
+            .read_to_string(&mut decompressed)
+            .map_err(TextGenError::Io)?;
+        decompressed.as_str()
+    } else {
+        std::str::from_utf8(bytes)
+            .map_err(|e| TextGenError::Scraper(format!("Sitemap is not UTF-8: {}", e)))?
+    };
+
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| TextGenError::Scraper(format!("Invalid sitemap XML: {}", e)))?;
+    let root = document.root_element();
+
+    let entry_tag = match root.tag_name().name() {
+        "urlset" => "url",
+        "sitemapindex" => "sitemap",
+        other => {
+            return Err(TextGenError::Scraper(format!(
+                "Unexpected sitemap root element <{}>",
+                other
+            )));
+        }
+    };
+
+    let entries = root
+        .children()
+        .filter(|node| node.has_tag_name(entry_tag))
+        .filter_map(|node| {
+            let child_text = |name: &str| {
+                node.children()
+                    .find(|child| child.has_tag_name(name))
+                    .and_then(|child| child.text())
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty())
+            };
+            Some(SitemapEntry {
+                loc: child_text("loc")?.to_string(),
+                lastmod: child_text("lastmod").and_then(parse_lastmod),
+            })
+        })
+        .collect();
+
+    Ok(if entry_tag == "url" {
+        Sitemap::Urls(entries)
+    } else {
+        Sitemap::Index(entries)
+    })
+}
+
+/// Parses a W3C datetime: a full RFC 3339 timestamp or a plain `YYYY-MM-DD` date
+pub fn parse_lastmod(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    const URLSET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/a</loc><lastmod>2024-03-01</lastmod></url>
+  <url>
+    <loc> https://example.com/b </loc>
+    <lastmod>2023-12-31T23:00:00+02:00</lastmod>
+  </url>
+  <url><loc>https://example.com/c</loc></url>
+  <url><lastmod>2024-01-01</lastmod></url>
+</urlset>"#;
+
+    #[test]
+    fn test_parse_urlset() {
+        let Sitemap::Urls(entries) = parse_sitemap(URLSET.as_bytes()).unwrap() else {
+            panic!("expected a urlset");
+        };
+
+        let locs: Vec<&str> = entries.iter().map(|entry| entry.loc.as_str()).collect();
+        assert_eq!(
+            locs,
+            [
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://example.com/c"
+            ]
+        );
+        assert_eq!(
+            entries[1].lastmod,
+            Some(Utc.with_ymd_and_hms(2023, 12, 31, 21, 0, 0).unwrap())
+        );
+
+        let since = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert!(entries[0].modified_since(since));
+        assert!(!entries[1].modified_since(since));
+        assert!(entries[2].modified_since(since));
+    }
+
+    #[test]
+    fn test_parse_gzipped_index() {
+        let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/posts.xml</loc><lastmod>2024-05-01T10:00:00Z</lastmod></sitemap>
+</sitemapindex>"#;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(index.as_bytes()).unwrap();
+
+        let sitemap = parse_sitemap(&encoder.finish().unwrap()).unwrap();
+
+        assert_eq!(
+            sitemap,
+            Sitemap::Index(vec![SitemapEntry {
+                loc: "https://example.com/posts.xml".to_string(),
+                lastmod: Some(Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap()),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_documents() {
+        assert!(parse_sitemap(b"<html><body>Not found</body></html>").is_err());
+        assert!(parse_sitemap(b"not xml").is_err());
+        assert_eq!(parse_lastmod("yesterday"), None);
+    }
+}
@@ -0,0 +1,410 @@
+use crate::error::{Result, TextGenError};
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+const DEFAULT_MAX_LINE_REPEATS: usize = 3;
+
+static CITATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\[(?:\d+(?:[,–-]\s*\d+)*|[a-z]|note \d+|edit|citation needed|clarification needed|who\?|when\?)\]",
+    )
+    .unwrap()
+});
+
+static URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)\b(?:https?://|www\.)[^\s<>]*[^\s<>.,;:!?)\]'"]|[\w.+-]+@[\w-]+(?:\.[\w-]+)+"#,
+    )
+    .unwrap()
+});
+
+static SECTION_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\[(?:chorus|verse|intro|outro|bridge|hook|pre-chorus|post-chorus|refrain|interlude|instrumental|break|skit|produced by)\b[^\]\n]*\]",
+    )
+    .unwrap()
+});
+
+static BOILERPLATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:we use cookies|cookie (?:policy|settings|preferences)|accept (?:all )?cookies|privacy policy|terms of (?:use|service)|all rights reserved|subscribe to our newsletter|sign up for our newsletter|you might also like|\d+ contributors?)\b",
+    )
+    .unwrap()
+});
+
+/// A single cleaning step applied to scraped text
+pub trait TextFilter: Send + Sync {
+    /// Name used to select the filter in configuration
+    fn name(&self) -> &str;
+
+    fn apply(&self, text: &str) -> String;
+}
+
+/// Removes citation markers such as "[12]", "[a]" and "[citation needed]"
+pub struct CitationFilter;
+
+impl TextFilter for CitationFilter {
+    fn name(&self) -> &str {
+        "citations"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        CITATION.replace_all(text, "").into_owned()
+    }
+}
+
+/// Removes URLs and email addresses
+pub struct UrlFilter;
+
+impl TextFilter for UrlFilter {
+    fn name(&self) -> &str {
+        "urls"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        URL.replace_all(text, "").into_owned()
+    }
+}
+
+/// Removes bracketed song section tags such as "[Chorus]" or "[Verse 1: Artist]"
+pub struct SectionTagFilter;
+
+impl TextFilter for SectionTagFilter {
+    fn name(&self) -> &str {
+        "section_tags"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        SECTION_TAG.replace_all(text, "").into_owned()
+    }
+}
+
+/// Drops lines that look like cookie banners, legal notices or page chrome
+pub struct BoilerplateFilter;
+
+impl TextFilter for BoilerplateFilter {
+    fn name(&self) -> &str {
+        "boilerplate"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        retain_lines(text, |line| {
+            // Only short lines are treated as boilerplate, prose may mention cookies
+            !(BOILERPLATE.is_match(line) && line.split_whitespace().count() <= 20)
+        })
+    }
+}
+
+/// Keeps at most `max_occurrences` copies of any non-empty line
+///
+/// Navigation menus and footers repeat on every scraped page. Not part of
+/// the standard pipeline, since it also trims song choruses.
+pub struct RepeatedLineFilter {
+    pub max_occurrences: usize,
+}
+
+impl Default for RepeatedLineFilter {
+    fn default() -> Self {
+        Self {
+            max_occurrences: DEFAULT_MAX_LINE_REPEATS,
+        }
+    }
+}
+
+impl TextFilter for RepeatedLineFilter {
+    fn name(&self) -> &str {
+        "repeated_lines"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut dropped = 0;
+        let kept = retain_lines(text, |line| {
+            let key = line.trim().to_lowercase();
+            if key.is_empty() {
+                return true;
+            }
+            let count = seen.entry(key).or_insert(0);
+            *count += 1;
+            let keep = *count <= self.max_occurrences;
+            dropped += usize::from(!keep);
+            keep
+        });
+        if dropped > 0 {
+            info!(
+                "Dropped {} lines repeated more than {} times",
+                dropped, self.max_occurrences
+            );
+        }
+        kept
+    }
+}
+
+/// Removes emoji, symbols and control characters, keeping letters, digits,
+/// whitespace and punctuation
+pub struct NonTextFilter;
+
+impl TextFilter for NonTextFilter {
+    fn name(&self) -> &str {
+        "non_text"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        text.chars()
+            .filter(|c| {
+                c.is_alphanumeric()
+                    || c.is_whitespace()
+                    || c.is_ascii_punctuation()
+                    || "«»“”‘’¿¡…–—".contains(*c)
+            })
+            .collect()
+    }
+}
+
+/// Replaces every match of a user-supplied pattern
+pub struct RegexFilter {
+    regex: Regex,
+    replacement: String,
+}
+
+impl RegexFilter {
+    pub fn new(pattern: &str, replacement: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            TextGenError::Config(format!("Invalid cleaning rule '{}': {}", pattern, e))
+        })?;
+
+        Ok(Self {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl TextFilter for RegexFilter {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.regex
+            .replace_all(text, self.replacement.as_str())
+            .into_owned()
+    }
+}
+
+fn retain_lines(text: &str, mut keep: impl FnMut(&str) -> bool) -> String {
+    text.lines()
+        .filter(|line| keep(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ordered list of filters applied between scraping and training
+pub struct CleaningPipeline {
+    filters: Vec<Box<dyn TextFilter>>,
+}
+
+impl CleaningPipeline {
+    /// Creates a pipeline without any filters
+    pub fn empty() -> Self {
+        Self {
+            filters: Vec::new(),
+        }
+    }
+
+    /// The built-in filters in their recommended order, except `repeated_lines`
+    pub fn standard() -> Self {
+        Self::empty()
+            .with_filter(CitationFilter)
+            .with_filter(UrlFilter)
+            .with_filter(SectionTagFilter)
+            .with_filter(BoilerplateFilter)
+            .with_filter(NonTextFilter)
+    }
+
+    pub fn with_filter(mut self, filter: impl TextFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Builds a pipeline from the `cleaning` section of scraper settings
+    ///
+    /// `"cleaning": false` disables cleaning, a missing section uses the
+    /// [standard](Self::standard) filters. Otherwise the object may contain `filters` (names of
+    /// built-in filters to run, defaulting to the standard ones),
+    /// `max_line_repeats` and `rules`, a list of
+    /// `{"pattern": ..., "replacement": ...}` regex rules applied last.
+    pub fn from_settings(settings: &serde_json::Value) -> Result<Self> {
+        let cleaning = &settings["cleaning"];
+
+        if cleaning.is_null() {
+            return Ok(Self::standard());
+        }
+        if cleaning.as_bool() == Some(false) {
+            return Ok(Self::empty());
+        }
+
+        let max_line_repeats = cleaning["max_line_repeats"]
+            .as_u64()
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_MAX_LINE_REPEATS);
+
+        let mut pipeline = Self::empty();
+
+        let names: Vec<&str> = match cleaning["filters"].as_array() {
+            Some(names) => names.iter().filter_map(|v| v.as_str()).collect(),
+            None => STANDARD_FILTERS.to_vec(),
+        };
+
+        for name in names {
+            pipeline = match name {
+                "citations" => pipeline.with_filter(CitationFilter),
+                "urls" => pipeline.with_filter(UrlFilter),
+                "section_tags" => pipeline.with_filter(SectionTagFilter),
+                "boilerplate" => pipeline.with_filter(BoilerplateFilter),
+                "repeated_lines" => pipeline.with_filter(RepeatedLineFilter {
+                    max_occurrences: max_line_repeats,
+                }),
+                "non_text" => pipeline.with_filter(NonTextFilter),
+                other => {
+                    return Err(TextGenError::Config(format!(
+                        "Unknown cleaning filter '{}'",
+                        other
+                    )));
+                }
+            };
+        }
+
+        if let Some(rules) = cleaning["rules"].as_array() {
+            for rule in rules {
+                let pattern = rule["pattern"].as_str().ok_or_else(|| {
+                    TextGenError::Config("Cleaning rule is missing a 'pattern'".to_string())
+                })?;
+                let replacement = rule["replacement"].as_str().unwrap_or_default();
+                pipeline = pipeline.with_filter(RegexFilter::new(pattern, replacement)?);
+            }
+        }
+
+        Ok(pipeline)
+    }
+
+    pub fn filter_names(&self) -> Vec<&str> {
+        self.filters.iter().map(|f| f.name()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn clean(&self, text: &str) -> String {
+        let mut cleaned = text.to_string();
+
+        for filter in &self.filters {
+            let before = cleaned.len();
+            cleaned = filter.apply(&cleaned);
+            debug!(
+                "Cleaning filter '{}' removed {} bytes",
+                filter.name(),
+                before.saturating_sub(cleaned.len())
+            );
+        }
+
+        cleaned
+    }
+}
+
+/// Names of the filters in [`CleaningPipeline::standard`]
+const STANDARD_FILTERS: &[&str] = &[
+    "citations",
+    "urls",
+    "section_tags",
+    "boilerplate",
+    "non_text",
+];
+
+impl Default for CleaningPipeline {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_citation_filter() {
+        let text = "Rust is fast.[1] It is safe[12][a] and popular.[citation needed] [edit]";
+        assert_eq!(
+            CitationFilter.apply(text),
+            "Rust is fast. It is safe and popular. "
+        );
+    }
+
+    #[test]
+    fn test_url_filter() {
+        let text = "See https://example.com/a?b=1 or www.rust-lang.org, mail me@example.org.";
+        assert_eq!(UrlFilter.apply(text), "See  or , mail .");
+    }
+
+    #[test]
+    fn test_section_tag_filter() {
+        let text = "[Chorus]\nLa la la\n[Verse 2: Someone]\nWords [twice]";
+        assert_eq!(SectionTagFilter.apply(text), "\nLa la la\n\nWords [twice]");
+    }
+
+    #[test]
+    fn test_boilerplate_filter() {
+        let text = "We use cookies to improve your experience.\nReal content here.\n© 2024 All rights reserved";
+        assert_eq!(BoilerplateFilter.apply(text), "Real content here.");
+    }
+
+    #[test]
+    fn test_repeated_line_filter() {
+        let filter = RepeatedLineFilter { max_occurrences: 2 };
+        let text = "Home\nchorus\nHome\nverse\nhome\n\n\nchorus";
+        assert_eq!(filter.apply(text), "Home\nchorus\nHome\nverse\n\n\nchorus");
+    }
+
+    #[test]
+    fn test_non_text_filter() {
+        let text = "Hello 👋 world!\u{200B} «Ça va?»\n★ done";
+        assert_eq!(NonTextFilter.apply(text), "Hello  world! «Ça va?»\n done");
+    }
+
+    #[test]
+    fn test_regex_filter() {
+        let filter = RegexFilter::new(r"\(\d{4}\)", "").unwrap();
+        assert_eq!(filter.apply("Born (1990) in Oslo"), "Born  in Oslo");
+        assert!(RegexFilter::new("(", "").is_err());
+    }
+
+    #[test]
+    fn test_pipeline_from_settings() {
+        let pipeline = CleaningPipeline::from_settings(&json!({})).unwrap();
+        assert_eq!(pipeline.filter_names(), STANDARD_FILTERS);
+        assert_eq!(
+            CleaningPipeline::standard().filter_names(),
+            STANDARD_FILTERS
+        );
+
+        let pipeline = CleaningPipeline::from_settings(&json!({ "cleaning": false })).unwrap();
+        assert!(pipeline.is_empty());
+
+        let settings = json!({
+            "cleaning": {
+                "filters": ["citations"],
+                "rules": [{ "pattern": "foo", "replacement": "bar" }]
+            }
+        });
+        let pipeline = CleaningPipeline::from_settings(&settings).unwrap();
+        assert_eq!(pipeline.filter_names(), ["citations", "regex"]);
+        assert_eq!(pipeline.clean("foo[3] baz"), "bar baz");
+
+        let settings = json!({ "cleaning": { "filters": ["nope"] } });
+        assert!(CleaningPipeline::from_settings(&settings).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use super::processing::is_heading;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_THRESHOLD: f64 = 0.8;
+const DEFAULT_SHINGLE_SIZE: usize = 3;
+const DEFAULT_MIN_LINE_WORDS: usize = 6;
+const NUM_HASHES: usize = 64;
+const BAND_ROWS: usize = 4;
+
+pub struct DedupOptions {
+    /// Estimated Jaccard similarity above which two units count as duplicates
+    pub threshold: f64,
+
+    /// Number of consecutive words per shingle
+    pub shingle_size: usize,
+
+    /// Whether repeats inside a single document (a song's chorus) are reduced
+    /// to their first occurrence instead of being left untouched
+    pub collapse_repeats: bool,
+
+    /// Lines and paragraphs with fewer words are never treated as duplicates
+    /// across documents
+    pub min_line_words: usize,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            shingle_size: DEFAULT_SHINGLE_SIZE,
+            collapse_repeats: false,
+            min_line_words: DEFAULT_MIN_LINE_WORDS,
+        }
+    }
+}
+
+/// Summary of what a deduplication pass removed
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DedupReport {
+    pub documents_removed: usize,
+    pub paragraphs_removed: usize,
+    pub lines_removed: usize,
+    pub repeats_collapsed: usize,
+    pub chars_before: usize,
+    pub chars_after: usize,
+}
+
+impl DedupReport {
+    pub fn removed_fraction(&self) -> f64 {
+        if self.chars_before == 0 {
+            return 0.0;
+        }
+        1.0 - self.chars_after as f64 / self.chars_before as f64
+    }
+}
+
+impl fmt::Display for DedupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "removed {} documents, {} paragraphs and {} lines, collapsed {} repeats ({:.1}% of text)",
+            self.documents_removed,
+            self.paragraphs_removed,
+            self.lines_removed,
+            self.repeats_collapsed,
+            self.removed_fraction() * 100.0
+        )
+    }
+}
+
+/// Removes exact and near-duplicate documents, paragraphs and lines
+///
+/// Documents are delimited by `#` heading lines and paragraphs by blank lines,
+/// matching the text produced by the scrapers. Near-duplicates are found with
+/// MinHash signatures over word shingles, indexed with locality-sensitive
+/// hashing so large corpora are not compared pairwise.
+pub struct Deduplicator {
+    options: DedupOptions,
+}
+
+/// A `#`-headed section of scraped text, split into paragraphs of lines
+pub(crate) struct Document<'a> {
+    pub(crate) heading: Option<&'a str>,
+    pub(crate) paragraphs: Vec<Vec<&'a str>>,
+}
+
+impl Deduplicator {
+    pub fn new(options: DedupOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn dedup(&self, text: &str) -> (String, DedupReport) {
+        let (mut deduplicated, report) = self.dedup_many(&[text]);
+        (deduplicated.remove(0), report)
+    }
+
+    /// Deduplicates several texts (e.g. input files) against each other
+    ///
+    /// Each text is returned separately, in the same order, so that callers
+    /// can keep training on them as independent documents.
+    pub fn dedup_many<S: AsRef<str>>(&self, texts: &[S]) -> (Vec<String>, DedupReport) {
+        let mut report = DedupReport::default();
+
+        let mut documents_index = MinHashIndex::new(self.options.threshold);
+        let mut paragraphs_index = MinHashIndex::new(self.options.threshold);
+        let mut seen_lines: HashMap<String, usize> = HashMap::new();
+        let mut results = Vec::with_capacity(texts.len());
+        let mut doc_id = 0;
+
+        for text in texts {
+            let text = text.as_ref();
+            report.chars_before += text.len();
+            let mut output: Vec<String> = Vec::new();
+
+            for document in split_documents(text) {
+                doc_id += 1;
+                let body: Vec<&str> = document.paragraphs.iter().flatten().copied().collect();
+                let signature = self.signature(&body.join(" "));
+
+                if let Some(signature) = &signature {
+                    if documents_index.find(signature).is_some() {
+                        report.documents_removed += 1;
+                        continue;
+                    }
+                    documents_index.insert(doc_id, signature.clone());
+                }
+
+                let mut kept_paragraphs = Vec::new();
+                let mut short_paragraphs = HashSet::new();
+                for paragraph in &document.paragraphs {
+                    let joined = paragraph.join(" ");
+                    let words = normalize_words(&joined);
+
+                    // Short refrains ("Oh oh") recur in unrelated songs
+                    if words.len() < self.options.min_line_words {
+                        if !short_paragraphs.insert(words.join(" "))
+                            && self.options.collapse_repeats
+                        {
+                            report.repeats_collapsed += 1;
+                            continue;
+                        }
+                    } else if let Some(signature) = self.signature(&joined) {
+                        match paragraphs_index.find(&signature) {
+                            Some(owner) if owner != doc_id => {
+                                report.paragraphs_removed += 1;
+                                continue;
+                            }
+                            Some(_) if self.options.collapse_repeats => {
+                                report.repeats_collapsed += 1;
+                                continue;
+                            }
+                            Some(_) => {}
+                            None => paragraphs_index.insert(doc_id, signature),
+                        }
+                    }
+
+                    let lines = self.dedup_lines(paragraph, doc_id, &mut seen_lines, &mut report);
+                    if !lines.is_empty() {
+                        kept_paragraphs.push(lines.join("\n"));
+                    }
+                }
+
+                let mut parts: Vec<String> =
+                    document.heading.map(String::from).into_iter().collect();
+                parts.extend(kept_paragraphs);
+                if !parts.is_empty() {
+                    output.push(parts.join("\n\n"));
+                }
+            }
+
+            let deduplicated = output.join("\n\n");
+            report.chars_after += deduplicated.len();
+            results.push(deduplicated);
+        }
+
+        (results, report)
+    }
+
+    fn dedup_lines<'a>(
+        &self,
+        paragraph: &[&'a str],
+        doc_id: usize,
+        seen_lines: &mut HashMap<String, usize>,
+        report: &mut DedupReport,
+    ) -> Vec<&'a str> {
+        let mut kept = Vec::new();
+        let mut previous: Option<String> = None;
+
+        for line in paragraph {
+            let key = normalize_words(line).join(" ");
+
+            if self.options.collapse_repeats && previous.as_deref() == Some(key.as_str()) {
+                report.repeats_collapsed += 1;
+                continue;
+            }
+
+            if key.split(' ').count() >= self.options.min_line_words {
+                match seen_lines.get(&key) {
+                    Some(owner) if *owner != doc_id => {
+                        report.lines_removed += 1;
+                        continue;
+                    }
+                    Some(_) => {}
+                    None => {
+                        seen_lines.insert(key.clone(), doc_id);
+                    }
+                }
+            }
+
+            previous = Some(key);
+            kept.push(*line);
+        }
+
+        kept
+    }
+
+    fn signature(&self, text: &str) -> Option<Vec<u64>> {
+        let words = normalize_words(text);
+        if words.is_empty() {
+            return None;
+        }
+
+        let shingles: HashSet<String> = if words.len() <= self.options.shingle_size {
+            HashSet::from([words.join(" ")])
+        } else {
+            words
+                .windows(self.options.shingle_size.max(1))
+                .map(|w| w.join(" "))
+                .collect()
+        };
+
+        let mut signature = vec![u64::MAX; NUM_HASHES];
+        for shingle in shingles {
+            let base = hash_one(&shingle);
+            for (i, slot) in signature.iter_mut().enumerate() {
+                let value = splitmix64(base ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                *slot = (*slot).min(value);
+            }
+        }
+
+        Some(signature)
+    }
+}
+
+impl Default for Deduplicator {
+    fn default() -> Self {
+        Self::new(DedupOptions::default())
+    }
+}
+
+/// Locality-sensitive index of MinHash signatures
+struct MinHashIndex {
+    threshold: f64,
+    signatures: Vec<(usize, Vec<u64>)>,
+    buckets: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl MinHashIndex {
+    fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            signatures: Vec::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Returns the owner of a stored signature similar to `signature`
+    fn find(&self, signature: &[u64]) -> Option<usize> {
+        let mut checked = HashSet::new();
+
+        for (band, rows) in signature.chunks(BAND_ROWS).enumerate() {
+            let Some(candidates) = self.buckets.get(&(band, hash_one(rows))) else {
+                continue;
+            };
+            for &candidate in candidates {
+                if !checked.insert(candidate) {
+                    continue;
+                }
+                let (owner, stored) = &self.signatures[candidate];
+                if similarity(signature, stored) >= self.threshold {
+                    return Some(*owner);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn insert(&mut self, owner: usize, signature: Vec<u64>) {
+        let idx = self.signatures.len();
+        for (band, rows) in signature.chunks(BAND_ROWS).enumerate() {
+            self.buckets
+                .entry((band, hash_one(rows)))
+                .or_default()
+                .push(idx);
+        }
+        self.signatures.push((owner, signature));
+    }
+}
+
+fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    let matching = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    matching as f64 / a.len() as f64
+}
+
+pub(crate) fn split_documents(text: &str) -> Vec<Document<'_>> {
+    let mut documents = Vec::new();
+    let mut current = Document {
+        heading: None,
+        paragraphs: Vec::new(),
+    };
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            if !paragraph.is_empty() {
+                current.paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            continue;
+        }
+
+        if is_heading(trimmed) {
+            if !paragraph.is_empty() {
+                current.paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            if current.heading.is_some() || !current.paragraphs.is_empty() {
+                documents.push(current);
+            }
+            current = Document {
+                heading: Some(trimmed),
+                paragraphs: Vec::new(),
+            };
+            continue;
+        }
+
+        paragraph.push(line);
+    }
+
+    if !paragraph.is_empty() {
+        current.paragraphs.push(paragraph);
+    }
+    if current.heading.is_some() || !current.paragraphs.is_empty() {
+        documents.push(current);
+    }
+
+    documents
+}
+
+fn normalize_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn hash_one<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removes_duplicate_documents() {
+        let text = "# A\n\nThe quick brown fox jumps over the lazy dog today.\n\n\
+                    # B\n\nThe quick brown fox jumps over the lazy dog today!\n\n\
+                    # C\n\nSomething completely different is written here.";
+        let (deduplicated, report) = Deduplicator::default().dedup(text);
+
+        assert_eq!(report.documents_removed, 1);
+        assert!(!deduplicated.contains("# B"));
+        assert!(deduplicated.contains("# C"));
+
+        // Deeper headings start documents too
+        let (deduplicated, report) = Deduplicator::default().dedup(&text.replace("# B", "## B"));
+        assert_eq!(report.documents_removed, 1);
+        assert!(!deduplicated.contains("## B"));
+    }
+
+    #[test]
+    fn test_removes_near_duplicate_paragraphs_across_documents() {
+        let shared = "Rust is a general purpose programming language emphasizing performance type safety and concurrency";
+        let text = format!(
+            "# Rust\n\n{}.\n\nIt was designed by Graydon Hoare.\n\n\
+             # Rust language\n\n{}, indeed.\n\nCargo is its package manager.",
+            shared, shared
+        );
+        let (deduplicated, report) = Deduplicator::default().dedup(&text);
+
+        assert_eq!(report.paragraphs_removed, 1);
+        assert_eq!(deduplicated.matches("general purpose").count(), 1);
+        assert!(deduplicated.contains("Cargo is its package manager."));
+    }
+
+    #[test]
+    fn test_collapse_repeats_inside_document() {
+        let text = "# Song\n\nNa na na hey\nNa na na hey\n\nverse one goes here\n\nNa na na hey\nNa na na hey";
+
+        let (kept, report) = Deduplicator::default().dedup(text);
+        assert_eq!(kept, text);
+        assert_eq!(
+            report,
+            DedupReport {
+                chars_before: text.len(),
+                chars_after: text.len(),
+                ..Default::default()
+            }
+        );
+
+        let collapsing = Deduplicator::new(DedupOptions {
+            collapse_repeats: true,
+            ..Default::default()
+        });
+        let (collapsed, report) = collapsing.dedup(text);
+        assert_eq!(collapsed, "# Song\n\nNa na na hey\n\nverse one goes here");
+        assert_eq!(report.repeats_collapsed, 2);
+    }
+
+    #[test]
+    fn test_keeps_short_paragraphs_across_documents() {
+        let text = "# First song\n\nOh oh\n\nThe river runs down to the sea tonight\n\n\
+                    # Second song\n\nOh oh\n\nMountains stand so tall against the morning sky";
+        let (deduplicated, report) = Deduplicator::default().dedup(text);
+
+        assert_eq!(deduplicated, text);
+        assert_eq!(report.paragraphs_removed, 0);
+
+        let collapsing = Deduplicator::new(DedupOptions {
+            collapse_repeats: true,
+            ..Default::default()
+        });
+        let (collapsed, _) = collapsing.dedup("# Song\n\nOh oh\n\nA verse in between\n\nOh oh");
+        assert_eq!(collapsed, "# Song\n\nOh oh\n\nA verse in between");
+    }
+
+    #[test]
+    fn test_dedup_many_keeps_texts_separate() {
+        let texts = [
+            "A line that is long enough to be compared across files.\n\nFirst file only.",
+            "A line that is long enough to be compared across files.\n\nSecond file only.",
+        ];
+        let (deduplicated, report) = Deduplicator::default().dedup_many(&texts);
+
+        assert_eq!(deduplicated.len(), 2);
+        assert!(deduplicated[0].contains("compared across files"));
+        assert_eq!(deduplicated[1], "Second file only.");
+        assert_eq!(report.paragraphs_removed, 1);
+    }
+
+    #[test]
+    fn test_report_display() {
+        let report = DedupReport {
+            documents_removed: 1,
+            chars_before: 200,
+            chars_after: 150,
+            ..Default::default()
+        };
+        assert_eq!(
+            report.to_string(),
+            "removed 1 documents, 0 paragraphs and 0 lines, collapsed 0 repeats (25.0% of text)"
+        );
+    }
+}
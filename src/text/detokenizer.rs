@@ -0,0 +1,550 @@
+use super::processing::{PARAGRAPH_MARKER, SECTION_MARKER, SENTENCE_MARKER, is_clitic};
+
+/// Heading marker left in the token stream when paragraphs are not preserved
+const LEGACY_HEADING: &str = "#";
+
+/// Punctuation and spacing rules for a single language
+///
+/// All fields are public so callers can build profiles for languages that
+/// are not covered by the built-in constructors.
+#[derive(Clone, Debug)]
+pub struct LanguageProfile {
+    /// Short language code, e.g. "en"
+    pub code: &'static str,
+
+    /// Marks that end a sentence and trigger capitalization of the next word
+    pub sentence_terminators: &'static [&'static str],
+
+    /// Marks attached to the preceding token without a space
+    pub closing_punctuation: &'static [&'static str],
+
+    /// Closing marks that are nevertheless preceded by a space (French "?")
+    pub spaced_punctuation: &'static [&'static str],
+
+    /// Marks attached to the following token without a space
+    pub opening_punctuation: &'static [&'static str],
+
+    /// Bracket pairs as (open, close)
+    pub brackets: &'static [(&'static str, &'static str)],
+
+    /// Quote pairs as (open, close); identical strings mean a symmetric quote
+    pub quotes: &'static [(&'static str, &'static str)],
+
+    /// Whether quoted text is padded with spaces inside the quotes (« Bonjour »)
+    pub pad_quotes: bool,
+
+    /// Inverted marks inserted at the start of a sentence, keyed by its terminator
+    pub inverted_marks: &'static [(&'static str, &'static str)],
+}
+
+const COMMON_CLOSING: &[&str] = &[".", ",", "!", "?", ";", ":", "…"];
+const COMMON_TERMINATORS: &[&str] = &[".", "!", "?", "…"];
+const COMMON_BRACKETS: &[(&str, &str)] = &[("(", ")"), ("[", "]"), ("{", "}")];
+
+impl LanguageProfile {
+    pub fn english() -> Self {
+        Self {
+            code: "en",
+            sentence_terminators: COMMON_TERMINATORS,
+            closing_punctuation: COMMON_CLOSING,
+            spaced_punctuation: &[],
+            opening_punctuation: &[],
+            brackets: COMMON_BRACKETS,
+            quotes: &[("\"", "\""), ("'", "'"), ("“", "”"), ("‘", "’")],
+            pad_quotes: false,
+            inverted_marks: &[],
+        }
+    }
+
+    pub fn french() -> Self {
+        Self {
+            code: "fr",
+            spaced_punctuation: &["!", "?", ";", ":"],
+            quotes: &[("«", "»"), ("\"", "\"")],
+            pad_quotes: true,
+            ..Self::english()
+        }
+    }
+
+    pub fn spanish() -> Self {
+        Self {
+            code: "es",
+            opening_punctuation: &["¿", "¡"],
+            quotes: &[("«", "»"), ("\"", "\""), ("“", "”")],
+            inverted_marks: &[("?", "¿"), ("!", "¡")],
+            ..Self::english()
+        }
+    }
+
+    /// Looks up a built-in profile by language code
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "en" | "english" => Some(Self::english()),
+            "fr" | "french" => Some(Self::french()),
+            "es" | "spanish" => Some(Self::spanish()),
+            _ => None,
+        }
+    }
+
+    fn is_terminator(&self, token: &str) -> bool {
+        self.sentence_terminators.contains(&token)
+    }
+
+    fn ends_with_terminator(&self, text: &str) -> bool {
+        self.sentence_terminators.iter().any(|t| text.ends_with(t))
+    }
+}
+
+impl Default for LanguageProfile {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// Turns token sequences back into readable text
+///
+/// `detokenize` produces prose for generated output: sentences are
+/// capitalized, brackets and quotes are balanced and the text ends with a
+/// terminator. `join` is the exact inverse of tokenization and only restores
+/// spacing.
+#[derive(Clone, Debug)]
+pub struct Detokenizer {
+    profile: LanguageProfile,
+    headings: bool,
+}
+
+enum PairRole {
+    Open(&'static str),
+    Close,
+    Unmatched,
+}
+
+struct Renderer<'a> {
+    profile: &'a LanguageProfile,
+    prose: bool,
+    out: String,
+    attach_next: bool,
+    capitalize_next: bool,
+    sentence_start: Option<usize>,
+    open_pairs: Vec<&'static str>,
+    headings: bool,
+    in_heading: bool,
+    skip_heading: bool,
+}
+
+impl Default for Detokenizer {
+    fn default() -> Self {
+        Self::new(LanguageProfile::default())
+    }
+}
+
+impl Detokenizer {
+    pub fn new(profile: LanguageProfile) -> Self {
+        Self {
+            profile,
+            headings: true,
+        }
+    }
+
+    /// Whether section headings are rendered as `# Title` lines or dropped
+    pub fn with_headings(mut self, headings: bool) -> Self {
+        self.headings = headings;
+        self
+    }
+
+    pub fn profile(&self) -> &LanguageProfile {
+        &self.profile
+    }
+
+    /// Formats generated tokens as readable prose
+    pub fn detokenize(&self, tokens: &[String]) -> String {
+        self.render(tokens, true)
+    }
+
+    /// Restores the spacing of tokenized text without altering any token
+    pub fn join(&self, tokens: &[String]) -> String {
+        self.render(tokens, false)
+    }
+
+    fn render(&self, tokens: &[String], prose: bool) -> String {
+        let mut renderer = Renderer {
+            profile: &self.profile,
+            prose,
+            out: String::new(),
+            attach_next: true,
+            capitalize_next: prose,
+            sentence_start: None,
+            open_pairs: Vec::new(),
+            headings: self.headings,
+            in_heading: false,
+            skip_heading: false,
+        };
+
+        for (i, token) in tokens.iter().enumerate() {
+            renderer.push(token, i + 1 == tokens.len());
+        }
+
+        renderer.finish()
+    }
+}
+
+impl Renderer<'_> {
+    fn push(&mut self, token: &str, is_last: bool) {
+        if token.eq_ignore_ascii_case(SENTENCE_MARKER) {
+            if self.prose && !self.out.is_empty() && !self.profile.ends_with_terminator(&self.out) {
+                self.out.push('.');
+            }
+            self.end_sentence(".");
+            return;
+        }
+
+        if token.eq_ignore_ascii_case(PARAGRAPH_MARKER) {
+            self.break_paragraph();
+            return;
+        }
+
+        if token.eq_ignore_ascii_case(SECTION_MARKER) {
+            self.start_section();
+            return;
+        }
+
+        if self.skip_heading {
+            return;
+        }
+
+        if self.prose && token == LEGACY_HEADING && !is_last {
+            self.close_pairs();
+            if !self.out.is_empty() && !self.out.ends_with('\n') {
+                self.out.push_str("\n\n");
+            }
+            self.attach_next = true;
+            self.end_sentence("");
+            return;
+        }
+
+        if (is_clitic(token) || self.is_possessive(token)) && !self.out.is_empty() {
+            self.out.push_str(token);
+            self.attach_next = false;
+            return;
+        }
+
+        match self.pair_role(token) {
+            Some(PairRole::Open(close)) => {
+                self.push_word(token);
+                self.open_pairs.push(close);
+                self.attach_next = !(self.profile.pad_quotes && self.is_quote(token));
+                return;
+            }
+            Some(PairRole::Close) => {
+                self.close_through(token);
+                return;
+            }
+            Some(PairRole::Unmatched) => return,
+            None => {}
+        }
+
+        if self.profile.opening_punctuation.contains(&token) {
+            self.push_word(token);
+            self.attach_next = true;
+            return;
+        }
+
+        if self.profile.closing_punctuation.contains(&token) {
+            if self.profile.spaced_punctuation.contains(&token) && !self.out.is_empty() {
+                self.out.push(' ');
+            }
+            self.out.push_str(token);
+            self.attach_next = false;
+            if self.profile.is_terminator(token) {
+                self.end_sentence(token);
+            }
+            return;
+        }
+
+        self.push_word(token);
+        self.attach_next = false;
+
+        // Tokens such as "world!" carry their terminator with them
+        if let Some(terminator) = self
+            .profile
+            .sentence_terminators
+            .iter()
+            .find(|t| token.len() > t.len() && token.ends_with(*t))
+        {
+            self.end_sentence(terminator);
+        }
+    }
+
+    fn break_paragraph(&mut self) {
+        if self.prose {
+            if self.in_heading || self.skip_heading {
+                self.in_heading = false;
+                self.skip_heading = false;
+            } else {
+                self.terminate_paragraph();
+            }
+        }
+
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push_str("\n\n");
+        }
+        self.attach_next = true;
+        self.end_sentence("");
+    }
+
+    fn start_section(&mut self) {
+        if self.prose {
+            if !self.in_heading && !self.skip_heading {
+                self.terminate_paragraph();
+            }
+            if !self.headings {
+                self.skip_heading = true;
+                self.in_heading = false;
+                return;
+            }
+            self.skip_heading = false;
+            self.in_heading = true;
+        }
+
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push_str("\n\n");
+        }
+        self.out.push_str("# ");
+        self.attach_next = true;
+        self.end_sentence("");
+    }
+
+    /// Closes open pairs and adds a terminator before a paragraph ends
+    fn terminate_paragraph(&mut self) {
+        self.close_pairs();
+        if !self.out.is_empty()
+            && !self.out.ends_with('\n')
+            && !self.profile.ends_with_terminator(&self.out)
+        {
+            self.out.push('.');
+            self.end_sentence(".");
+        }
+    }
+
+    fn push_word(&mut self, token: &str) {
+        if !self.attach_next && !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push(' ');
+        }
+        if self.sentence_start.is_none() {
+            self.sentence_start = Some(self.out.len());
+        }
+
+        if self.capitalize_next {
+            self.out.push_str(&capitalize_first(token));
+        } else {
+            self.out.push_str(token);
+        }
+
+        if token.chars().any(char::is_alphanumeric) {
+            self.capitalize_next = false;
+        }
+    }
+
+    fn end_sentence(&mut self, terminator: &str) {
+        if self.prose {
+            self.insert_inverted_mark(terminator);
+        }
+        self.sentence_start = None;
+        self.capitalize_next = self.prose;
+    }
+
+    fn insert_inverted_mark(&mut self, terminator: &str) {
+        let Some(start) = self.sentence_start else {
+            return;
+        };
+        let Some((_, mark)) = self
+            .profile
+            .inverted_marks
+            .iter()
+            .find(|(t, _)| *t == terminator)
+        else {
+            return;
+        };
+
+        if !self.out[start..].contains(mark) {
+            self.out.insert_str(start, mark);
+        }
+    }
+
+    fn pair_role(&self, token: &str) -> Option<PairRole> {
+        let pairs = self.profile.brackets.iter().chain(self.profile.quotes);
+
+        for (open, close) in pairs {
+            let expected = self.open_pairs.contains(close);
+            if token == *close && (open != close || expected) {
+                return Some(if expected || !self.prose {
+                    PairRole::Close
+                } else {
+                    PairRole::Unmatched
+                });
+            }
+            if token == *open {
+                return Some(PairRole::Open(close));
+            }
+        }
+
+        None
+    }
+
+    /// An apostrophe after a word ending in "s" ("James' car") that closes no open quote
+    fn is_possessive(&self, token: &str) -> bool {
+        matches!(token, "'" | "’")
+            && !self.open_pairs.contains(&token)
+            && !self.attach_next
+            && self.out.ends_with(['s', 'S'])
+    }
+
+    fn is_quote(&self, token: &str) -> bool {
+        self.profile
+            .quotes
+            .iter()
+            .any(|(open, close)| token == *open || token == *close)
+    }
+
+    /// Emits `token` as a closing mark, first closing any pairs opened after it
+    fn close_through(&mut self, token: &str) {
+        while let Some(close) = self.open_pairs.pop() {
+            if close == token {
+                break;
+            }
+            if self.prose {
+                self.push_closer(close);
+            }
+        }
+        self.push_closer(token);
+    }
+
+    fn push_closer(&mut self, close: &str) {
+        if self.profile.pad_quotes && self.is_quote(close) && !self.out.is_empty() {
+            self.out.push(' ');
+        }
+        self.out.push_str(close);
+        self.attach_next = false;
+    }
+
+    fn close_pairs(&mut self) {
+        while let Some(close) = self.open_pairs.pop() {
+            self.push_closer(close);
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.prose {
+            return self.out;
+        }
+
+        let trimmed_len = self.out.trim_end().len();
+        self.out.truncate(trimmed_len);
+        if self.in_heading {
+            self.close_pairs();
+            // Drop a heading marker that never received a title
+            if self.out == "#" || self.out.ends_with("\n#") {
+                self.out.pop();
+            }
+        } else {
+            self.terminate_paragraph();
+        }
+
+        self.out.trim().to_string()
+    }
+}
+
+/// Capitalizes the first letter, after any leading apostrophe ("'tis" -> "'Tis")
+fn capitalize_first(token: &str) -> String {
+    let apostrophes = token.len() - token.trim_start_matches(['\'', '’']).len();
+    let (prefix, word) = token.split_at(apostrophes);
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => prefix.to_string() + &c.to_uppercase().collect::<String>() + chars.as_str(),
+        None => token.to_string(),
+    }
+}
+
+/// Joins tokens back into text, inverting [`tokenize`](super::tokenize).
+///
+/// Uses the English profile; see [`Detokenizer::join`] for other languages.
+/// For text tokenized without lowercasing this reproduces the normalized input.
+pub fn detokenize(tokens: &[String]) -> String {
+    Detokenizer::default().join(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_prose_capitalizes_and_terminates() {
+        let detokenizer = Detokenizer::default();
+        let text = detokenizer.detokenize(&tokens("hello , world . this is <sentence> fine"));
+
+        assert_eq!(text, "Hello, world. This is. Fine.");
+    }
+
+    #[test]
+    fn test_prose_balances_pairs() {
+        let detokenizer = Detokenizer::default();
+
+        let text = detokenizer.detokenize(&tokens("he said ( \" stop now"));
+        assert_eq!(text, "He said (\"stop now\").");
+
+        let text = detokenizer.detokenize(&tokens("stray ) bracket ."));
+        assert_eq!(text, "Stray bracket.");
+    }
+
+    #[test]
+    fn test_french_spacing() {
+        let detokenizer = Detokenizer::new(LanguageProfile::french());
+        let text = detokenizer.detokenize(&tokens("il a dit « bonjour » ? oui !"));
+
+        assert_eq!(text, "Il a dit « bonjour » ? Oui !");
+    }
+
+    #[test]
+    fn test_spanish_inverted_marks() {
+        let detokenizer = Detokenizer::new(LanguageProfile::spanish());
+        let text = detokenizer.detokenize(&tokens("hola . cómo estás ? ¡ qué bien !"));
+
+        assert_eq!(text, "Hola. ¿Cómo estás? ¡Qué bien!");
+    }
+
+    #[test]
+    fn test_prose_paragraphs_and_headings() {
+        let detokenizer = Detokenizer::default();
+        let input = tokens("<section> rust <paragraph> it is fast <paragraph> it is safe");
+
+        assert_eq!(
+            detokenizer.detokenize(&input),
+            "# Rust\n\nIt is fast.\n\nIt is safe."
+        );
+
+        let detokenizer = Detokenizer::default().with_headings(false);
+        assert_eq!(detokenizer.detokenize(&input), "It is fast.\n\nIt is safe.");
+    }
+
+    #[test]
+    fn test_prose_possessives_and_leading_apostrophes() {
+        let detokenizer = Detokenizer::default();
+        let text = detokenizer.detokenize(&tokens(
+            "'twas the dogs ' bowls . ' yes ' , said james ' son",
+        ));
+
+        assert_eq!(text, "'Twas the dogs' bowls. 'Yes', said james' son.");
+    }
+
+    #[test]
+    fn test_join_keeps_tokens_verbatim() {
+        let detokenizer = Detokenizer::default();
+        let text = detokenizer.join(&tokens("stray ) bracket"));
+
+        assert_eq!(text, "stray) bracket");
+    }
+}
@@ -0,0 +1,410 @@
+use super::dedup::split_documents;
+use crate::error::{Result, TextGenError};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+const MAX_NGRAM: usize = 3;
+const PROFILE_SIZE: usize = 300;
+const MIN_LETTERS: usize = 20;
+
+/// Reference texts the built-in profiles are computed from
+const SAMPLES: &[(&str, &str)] = &[
+    (
+        "en",
+        "All human beings are born free and equal in dignity and rights. They are endowed with \
+         reason and conscience and should act towards one another in a spirit of brotherhood. \
+         Everyone is entitled to all the rights and freedoms set forth in this declaration, \
+         without distinction of any kind, such as race, colour, sex, language, religion, \
+         political or other opinion, national or social origin, property, birth or other status. \
+         Everyone has the right to life, liberty and security of person. No one shall be held in \
+         slavery or servitude. The history of the town is closely linked with the river, which \
+         brought trade and people who settled there when the first bridge was built. It was the \
+         most important thing that they had ever seen, and they would always remember it.",
+    ),
+    (
+        "fr",
+        "Tous les êtres humains naissent libres et égaux en dignité et en droits. Ils sont doués \
+         de raison et de conscience et doivent agir les uns envers les autres dans un esprit de \
+         fraternité. Chacun peut se prévaloir de tous les droits et de toutes les libertés \
+         proclamés dans la présente déclaration, sans distinction aucune, notamment de race, de \
+         couleur, de sexe, de langue, de religion, d'opinion politique ou de toute autre opinion. \
+         Tout individu a droit à la vie, à la liberté et à la sûreté de sa personne. Nul ne sera \
+         tenu en esclavage ni en servitude. L'histoire de la ville est étroitement liée à la \
+         rivière, qui apportait le commerce et les gens qui s'y sont installés quand le premier \
+         pont a été construit. C'était la chose la plus importante qu'ils avaient jamais vue.",
+    ),
+    (
+        "es",
+        "Todos los seres humanos nacen libres e iguales en dignidad y derechos y, dotados como \
+         están de razón y conciencia, deben comportarse fraternalmente los unos con los otros. \
+         Toda persona tiene todos los derechos y libertades proclamados en esta declaración, sin \
+         distinción alguna de raza, color, sexo, idioma, religión, opinión política o de \
+         cualquier otra índole, origen nacional o social, posición económica, nacimiento o \
+         cualquier otra condición. Todo individuo tiene derecho a la vida, a la libertad y a la \
+         seguridad de su persona. Nadie estará sometido a esclavitud ni a servidumbre. La \
+         historia de la ciudad está muy ligada al río, que trajo el comercio y la gente que se \
+         quedó allí cuando se construyó el primer puente. Era lo más importante que habían visto.",
+    ),
+    (
+        "de",
+        "Alle Menschen sind frei und gleich an Würde und Rechten geboren. Sie sind mit Vernunft \
+         und Gewissen begabt und sollen einander im Geist der Brüderlichkeit begegnen. Jeder hat \
+         Anspruch auf die in dieser Erklärung verkündeten Rechte und Freiheiten ohne irgendeinen \
+         Unterschied, etwa nach Rasse, Hautfarbe, Geschlecht, Sprache, Religion, politischer oder \
+         sonstiger Überzeugung, nationaler oder sozialer Herkunft, Vermögen, Geburt oder sonstigem \
+         Stand. Jeder hat das Recht auf Leben, Freiheit und Sicherheit der Person. Niemand darf \
+         in Sklaverei oder Leibeigenschaft gehalten werden. Die Geschichte der Stadt ist eng mit \
+         dem Fluss verbunden, der Handel und Menschen brachte, die sich dort niederließen, als \
+         die erste Brücke gebaut wurde. Es war das Wichtigste, was sie je gesehen hatten.",
+    ),
+    (
+        "it",
+        "Tutti gli esseri umani nascono liberi ed eguali in dignità e diritti. Essi sono dotati \
+         di ragione e di coscienza e devono agire gli uni verso gli altri in spirito di \
+         fratellanza. Ad ogni individuo spettano tutti i diritti e tutte le libertà enunciate \
+         nella presente dichiarazione, senza distinzione alcuna, per ragioni di razza, di colore, \
+         di sesso, di lingua, di religione, di opinione politica o di altro genere, di origine \
+         nazionale o sociale, di ricchezza, di nascita o di altra condizione. Ogni individuo ha \
+         diritto alla vita, alla libertà ed alla sicurezza della propria persona. Nessun \
+         individuo potrà essere tenuto in stato di schiavitù o di servitù. La storia della città \
+         è strettamente legata al fiume, che portò il commercio e le persone che si stabilirono \
+         lì quando fu costruito il primo ponte. Era la cosa più importante che avessero mai visto.",
+    ),
+    (
+        "pt",
+        "Todos os seres humanos nascem livres e iguais em dignidade e em direitos. Dotados de \
+         razão e de consciência, devem agir uns para com os outros em espírito de fraternidade. \
+         Todos os seres humanos podem invocar os direitos e as liberdades proclamados na presente \
+         declaração, sem distinção alguma, nomeadamente de raça, de cor, de sexo, de língua, de \
+         religião, de opinião política ou outra, de origem nacional ou social, de fortuna, de \
+         nascimento ou de qualquer outra situação. Todo o indivíduo tem direito à vida, à \
+         liberdade e à segurança pessoal. Ninguém será mantido em escravatura ou em servidão. A \
+         história da cidade está muito ligada ao rio, que trouxe o comércio e as pessoas que se \
+         fixaram ali quando a primeira ponte foi construída. Foi a coisa mais importante que \
+         eles já tinham visto.",
+    ),
+    (
+        "fi",
+        "Kaikki ihmiset syntyvät vapaina ja tasavertaisina arvoltaan ja oikeuksiltaan. Heille on \
+         annettu järki ja omatunto, ja heidän on toimittava toisiaan kohtaan veljeyden hengessä. \
+         Jokainen on oikeutettu kaikkiin tässä julistuksessa esitettyihin oikeuksiin ja \
+         vapauksiin ilman minkäänlaista rotuun, väriin, sukupuoleen, kieleen, uskontoon, \
+         poliittiseen tai muuhun mielipiteeseen, kansalliseen tai yhteiskunnalliseen \
+         alkuperään, omaisuuteen, syntyperään tai muuhun tekijään perustuvaa erotusta. \
+         Jokaisella on oikeus elämään, vapauteen ja henkilökohtaiseen turvallisuuteen. Ketään ei \
+         saa pitää orjana tai orjuutettuna. Kaupungin historia on kiinteästi sidoksissa jokeen, \
+         joka toi kauppaa ja ihmisiä, jotka asettuivat sinne, kun ensimmäinen silta \
+         rakennettiin. Se oli tärkein asia, jonka he olivat koskaan nähneet.",
+    ),
+    (
+        "ru",
+        "Все люди рождаются свободными и равными в своем достоинстве и правах. Они наделены \
+         разумом и совестью и должны поступать в отношении друг друга в духе братства. Каждый \
+         человек должен обладать всеми правами и всеми свободами, провозглашенными настоящей \
+         декларацией, без какого бы то ни было различия, как-то в отношении расы, цвета кожи, \
+         пола, языка, религии, политических или иных убеждений, национального или социального \
+         происхождения, имущественного, сословного или иного положения. Каждый человек имеет \
+         право на жизнь, на свободу и на личную неприкосновенность. Никто не должен содержаться \
+         в рабстве или в подневольном состоянии. История города тесно связана с рекой, которая \
+         принесла торговлю и людей, поселившихся там, когда был построен первый мост.",
+    ),
+];
+
+/// Ranked character n-grams of a text, as in Cavnar & Trenkle's classifier
+#[derive(Clone, Debug)]
+pub struct NgramProfile {
+    ranks: HashMap<String, usize>,
+}
+
+impl NgramProfile {
+    pub fn from_text(text: &str) -> Self {
+        let ranks = top_ngrams(text)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, ngram)| (ngram, rank))
+            .collect();
+        Self { ranks }
+    }
+
+    /// "Out-of-place" distance between a document's ranked n-grams and this profile
+    fn distance(&self, ngrams: &[String]) -> usize {
+        ngrams
+            .iter()
+            .enumerate()
+            .map(|(rank, ngram)| match self.ranks.get(ngram) {
+                Some(profile_rank) => rank.abs_diff(*profile_rank),
+                None => PROFILE_SIZE,
+            })
+            .sum()
+    }
+}
+
+/// Identifies the language of a text from character n-gram statistics
+///
+/// Profiles for en, fr, es, de, it, pt, fi and ru are built in; more can be added
+/// from sample text with [`LanguageDetector::with_profile`]. Detection is
+/// entirely local and works best on a few sentences or more.
+#[derive(Clone, Debug)]
+pub struct LanguageDetector {
+    profiles: Vec<(String, NgramProfile)>,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> Self {
+        Self {
+            profiles: SAMPLES
+                .iter()
+                .map(|(code, sample)| (code.to_string(), NgramProfile::from_text(sample)))
+                .collect(),
+        }
+    }
+}
+
+impl LanguageDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) the profile for `code`, computed from `sample`
+    pub fn with_profile(mut self, code: &str, sample: &str) -> Self {
+        let profile = NgramProfile::from_text(sample);
+        match self.profiles.iter_mut().find(|(c, _)| c == code) {
+            Some((_, existing)) => *existing = profile,
+            None => self.profiles.push((code.to_string(), profile)),
+        }
+        self
+    }
+
+    pub fn languages(&self) -> Vec<&str> {
+        self.profiles
+            .iter()
+            .map(|(code, _)| code.as_str())
+            .collect()
+    }
+
+    /// Returns the code of the closest language, or `None` for text with too few letters
+    pub fn detect(&self, text: &str) -> Option<&str> {
+        if text.chars().filter(|c| c.is_alphabetic()).count() < MIN_LETTERS {
+            return None;
+        }
+
+        let ngrams = top_ngrams(text);
+        self.profiles
+            .iter()
+            .min_by_key(|(_, profile)| profile.distance(&ngrams))
+            .map(|(code, _)| code.as_str())
+    }
+
+    /// Labels every `#`-headed document in `text` with its language
+    pub fn label_documents(&self, text: &str) -> Vec<LabeledDocument> {
+        split_documents(text)
+            .into_iter()
+            .map(|document| {
+                let body: Vec<&str> = document.paragraphs.iter().flatten().copied().collect();
+                let language = self.detect(&body.join(" ")).map(String::from);
+
+                let mut parts: Vec<String> =
+                    document.heading.map(String::from).into_iter().collect();
+                parts.extend(document.paragraphs.iter().map(|p| p.join("\n")));
+
+                LabeledDocument {
+                    heading: document.heading.map(String::from),
+                    language,
+                    text: parts.join("\n\n"),
+                }
+            })
+            .collect()
+    }
+
+    /// Keeps only documents detected as one of `allowed`
+    ///
+    /// Documents too short to identify are kept. Returns the filtered text
+    /// and the language mix of the input.
+    pub fn filter(&self, text: &str, allowed: &[String]) -> (String, LanguageReport) {
+        let documents = self.label_documents(text);
+        let report = LanguageReport::from_documents(&documents);
+
+        let kept: Vec<String> = documents
+            .into_iter()
+            .filter(|document| document.is_in(allowed))
+            .map(|document| document.text)
+            .collect();
+
+        (kept.join("\n\n"), report)
+    }
+}
+
+/// A document of scraped or input text together with its detected language
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabeledDocument {
+    pub heading: Option<String>,
+    pub language: Option<String>,
+    pub text: String,
+}
+
+impl LabeledDocument {
+    /// Returns true if the document is in one of `languages` or could not be identified
+    pub fn is_in(&self, languages: &[String]) -> bool {
+        match &self.language {
+            Some(language) => languages.iter().any(|l| l.eq_ignore_ascii_case(language)),
+            None => true,
+        }
+    }
+}
+
+/// Number of documents and characters per detected language
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LanguageReport {
+    /// Language code ("unknown" for undetected text) to (documents, characters)
+    pub languages: BTreeMap<String, (usize, usize)>,
+}
+
+impl LanguageReport {
+    pub fn from_documents(documents: &[LabeledDocument]) -> Self {
+        let mut languages = BTreeMap::new();
+        for document in documents {
+            let code = document.language.as_deref().unwrap_or("unknown");
+            let entry = languages.entry(code.to_string()).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += document.text.chars().count();
+        }
+        Self { languages }
+    }
+
+    /// The language with the most characters, if any text was detected
+    pub fn dominant(&self) -> Option<&str> {
+        self.languages
+            .iter()
+            .filter(|(code, _)| code.as_str() != "unknown")
+            .max_by_key(|(_, (_, chars))| *chars)
+            .map(|(code, _)| code.as_str())
+    }
+}
+
+impl fmt::Display for LanguageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: usize = self.languages.values().map(|(_, chars)| chars).sum();
+        let mix: Vec<String> = self
+            .languages
+            .iter()
+            .map(|(code, (documents, chars))| {
+                format!(
+                    "{} {:.1}% ({} documents)",
+                    code,
+                    *chars as f64 / total.max(1) as f64 * 100.0,
+                    documents
+                )
+            })
+            .collect();
+        write!(f, "{}", mix.join(", "))
+    }
+}
+
+/// Checks a list of language codes against the languages the detector knows
+pub fn validate_languages(detector: &LanguageDetector, codes: &[String]) -> Result<()> {
+    let known = detector.languages();
+    for code in codes {
+        if !known.iter().any(|k| k.eq_ignore_ascii_case(code)) {
+            return Err(TextGenError::Config(format!(
+                "Unknown language '{}' (known: {})",
+                code,
+                known.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The `PROFILE_SIZE` most frequent 1- to 3-grams of lowercased words padded with spaces
+fn top_ngrams(text: &str) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+    {
+        let padded: Vec<char> = format!(" {} ", word.to_lowercase()).chars().collect();
+        for n in 1..=MAX_NGRAM {
+            for window in padded.windows(n) {
+                if window == [' '] {
+                    continue;
+                }
+                *counts.entry(window.iter().collect()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+        .into_iter()
+        .take(PROFILE_SIZE)
+        .map(|(ngram, _)| ngram)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_builtin_languages() {
+        let detector = LanguageDetector::default();
+
+        let cases = [
+            (
+                "en",
+                "The weather was cold and the children stayed inside reading books all day.",
+            ),
+            (
+                "fr",
+                "Le temps était froid et les enfants sont restés à la maison pour lire des livres.",
+            ),
+            (
+                "es",
+                "El tiempo era frío y los niños se quedaron en casa leyendo libros todo el día.",
+            ),
+            (
+                "de",
+                "Das Wetter war kalt und die Kinder blieben den ganzen Tag drinnen und lasen Bücher.",
+            ),
+            (
+                "fi",
+                "Sää oli kylmä, ja lapset pysyivät koko päivän sisällä lukemassa kirjoja.",
+            ),
+            (
+                "ru",
+                "Погода была холодной, и дети весь день сидели дома и читали книги.",
+            ),
+        ];
+        for (expected, text) in cases {
+            assert_eq!(detector.detect(text), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_short_text_is_undetected() {
+        assert_eq!(LanguageDetector::default().detect("Hi there"), None);
+    }
+
+    #[test]
+    fn test_filter_documents() {
+        let text = "# Rust\n\nRust is a programming language that focuses on safety and speed.\n\n\
+                    # Rouille\n\nLa rouille est une couche d'oxydes de fer qui se forme sur le métal.";
+        let detector = LanguageDetector::default();
+        let (filtered, report) = detector.filter(text, &["en".to_string()]);
+
+        assert!(filtered.starts_with("# Rust"));
+        assert!(!filtered.contains("Rouille"));
+        assert_eq!(report.languages["fr"].0, 1);
+        assert_eq!(report.languages["en"].0, 1);
+    }
+
+    #[test]
+    fn test_validate_languages() {
+        let detector = LanguageDetector::default();
+        assert!(validate_languages(&detector, &["EN".to_string()]).is_ok());
+        assert!(validate_languages(&detector, &["fi".to_string()]).is_ok());
+        assert!(validate_languages(&detector, &["xx".to_string()]).is_err());
+    }
+}
@@ -1,3 +1,21 @@
+pub mod cleaning;
+pub mod dedup;
+pub mod detokenizer;
+pub mod language;
 pub mod processing;
+pub mod stemming;
+pub mod stopwords;
+pub mod truecase;
+pub mod vocabulary;
 
-pub use processing::{TokenizerOptions, normalize_text, tokenize};
+pub use cleaning::{CleaningPipeline, TextFilter};
+pub use dedup::{DedupOptions, DedupReport, Deduplicator};
+pub use detokenizer::{Detokenizer, LanguageProfile, detokenize};
+pub use language::{LanguageDetector, LanguageReport};
+pub use processing::{
+    PARAGRAPH_MARKER, SECTION_MARKER, SENTENCE_MARKER, TokenizerOptions, is_clitic, is_marker,
+    normalize_text, tokenize,
+};
+pub use stemming::{Stemmer, StemmingLanguage};
+pub use truecase::TrueCaser;
+pub use vocabulary::VocabularyFilter;
@@ -1,7 +1,10 @@
-use crate::error::{Result, TextGenError};
+use super::stemming::{Stemmer, StemmingLanguage};
+use crate::error::Result;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::borrow::Cow;
 
+#[derive(Clone, Debug)]
 pub struct TokenizerOptions {
     /// Whether to convert text to lowercase
     ///
@@ -20,6 +23,18 @@ pub struct TokenizerOptions {
     /// Setting this to true prevents the model from generating nonsensical
     /// transitions between sentences, but may make the model more rigid.
     pub preserve_sentence_boundaries: bool,
+
+    /// Whether to preserve paragraph breaks and section headings
+    ///
+    /// Setting this to true emits `<PARAGRAPH>` and `<SECTION>` markers so the
+    /// generated text is split into paragraphs and can contain headings.
+    pub preserve_paragraphs: bool,
+
+    /// Language whose stemmer reduces words to their stem, if any
+    ///
+    /// Stemming merges inflections ("runs", "running") into one token, which
+    /// shrinks the vocabulary at the cost of less natural generated text.
+    pub stemming: Option<StemmingLanguage>,
 }
 
 impl Default for TokenizerOptions {
@@ -28,33 +43,139 @@ impl Default for TokenizerOptions {
             lowercase: true,
             preserve_punctuation: true,
             preserve_sentence_boundaries: true,
+            preserve_paragraphs: true,
+            stemming: None,
         }
     }
 }
 
-static SENTENCE_BOUNDARY: Lazy<Regex> = Lazy::new(|| Regex::new("([.!?])\\s+([A-Z])").unwrap());
+/// Marks the boundary between two sentences
+pub const SENTENCE_MARKER: &str = "<SENTENCE>";
+
+/// Marks a paragraph break (a blank line in the source text)
+pub const PARAGRAPH_MARKER: &str = "<PARAGRAPH>";
 
-static PUNCTUATION: Lazy<Regex> = Lazy::new(|| Regex::new("([.,!?;:()\\[\\]{}\"'\\-])").unwrap());
+/// Marks the start of a section heading; the heading runs until the next paragraph break
+pub const SECTION_MARKER: &str = "<SECTION>";
 
-static WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new("\\s+").unwrap());
+/// Returns true if the token is one of the structural markers emitted by [`tokenize`]
+pub fn is_marker(token: &str) -> bool {
+    [SENTENCE_MARKER, PARAGRAPH_MARKER, SECTION_MARKER]
+        .iter()
+        .any(|marker| token.eq_ignore_ascii_case(marker))
+}
+
+/// Clitics split off the preceding word, Penn Treebank style ("don't" -> "do" + "n't").
+///
+/// Each clitic is kept as its own token so that detokenization can glue it
+/// back onto the previous word without guessing.
+const CLITICS: &[&str] = &["n't", "'s", "'m", "'d", "'ll", "'re", "'ve"];
 
-static TOKENIZER_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new("([.!?])\\s+([A-Z])|([.,!?;:()\\[\\]{}\"'\\-])|\\s+").unwrap());
+static TOKENIZER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        "(?m)(?P<end>[.!?])(?P<gap>\\s+)(?P<start>[A-Z])",
+        "|^(?P<heading>#+)[ \\t]+",
+        "|(?P<paragraph>[ \\t\\r]*\\n[ \\t\\r]*\\n\\s*)",
+        "|(?P<word_end>\\w)(?P<clitic>(?i:n['’]t|['’](?:s|m|d|ll|re|ve))\\b)",
+        "|\\B(?P<elision>['’][nN]['’])\\B",
+        "|\\B(?P<aphaeresis>['’](?i:tis|twas|twere|twill|em)\\b)",
+        "|(?P<punct>[.,!?;:()\\[\\]{}\"'\\-])",
+        "|\\s+"
+    ))
+    .unwrap()
+});
+
+/// Returns true if the token is a contraction suffix such as "n't" or "'s"
+/// that belongs to the preceding word.
+///
+/// A lone apostrophe after a plural ("dogs'") is decided by the detokenizer,
+/// since it may also close a quote.
+pub fn is_clitic(token: &str) -> bool {
+    let normalized = token.replace('’', "'").to_lowercase();
+    CLITICS.contains(&normalized.as_str())
+}
 
 pub fn tokenize(text: &str, options: &TokenizerOptions) -> Vec<String> {
-    let estimated_token_count = text.len() / 5;
-    let mut tokens = Vec::with_capacity(estimated_token_count);
+    split_tokens(&separate_tokens(text, options), options)
+}
+
+/// Tokenizes `text` as the start of a longer text that continues with `next`
+///
+/// The result matches the leading tokens [`tokenize`] produces for the joined
+/// text, including the sentence and paragraph markers at the join, without
+/// tokenizing `next`. `paragraph_break` tells whether the two parts are
+/// separate paragraphs.
+pub fn tokenize_before(
+    text: &str,
+    next: &str,
+    paragraph_break: bool,
+    options: &TokenizerOptions,
+) -> Vec<String> {
+    let text = text.trim_end();
+    let starts_sentence = next
+        .trim_start()
+        .starts_with(|c: char| c.is_ascii_uppercase());
+
+    let mut processed_text = match text.char_indices().next_back() {
+        // The sentence rule keeps the end punctuation on the preceding word
+        Some((index, end @ ('.' | '!' | '?'))) if starts_sentence => {
+            let mut processed_text = separate_tokens(&text[..index], options).into_owned();
+            processed_text.push(end);
+            if options.preserve_sentence_boundaries {
+                processed_text = format!("{} {}", processed_text, SENTENCE_MARKER);
+            }
+            processed_text
+        }
+        _ => separate_tokens(text, options).into_owned(),
+    };
+    if paragraph_break && options.preserve_paragraphs {
+        processed_text = format!("{} {}", processed_text, PARAGRAPH_MARKER);
+    }
 
-    let processed_text = TOKENIZER_REGEX.replace_all(text, |caps: &regex::Captures| {
-        if let (Some(end_punct), Some(start_char)) = (caps.get(1), caps.get(2)) {
+    split_tokens(&processed_text, options)
+}
+
+/// Rewrites `text` so that its tokens and markers are separated by whitespace
+fn separate_tokens<'t>(text: &'t str, options: &TokenizerOptions) -> Cow<'t, str> {
+    TOKENIZER_REGEX.replace_all(text, |caps: &regex::Captures| {
+        if let (Some(end_punct), Some(gap), Some(start_char)) =
+            (caps.name("end"), caps.name("gap"), caps.name("start"))
+        {
+            let mut replacement = end_punct.as_str().to_string();
             if options.preserve_sentence_boundaries {
-                return format!("{} <SENTENCE> {}", end_punct.as_str(), start_char.as_str());
+                replacement = format!("{} {}", replacement, SENTENCE_MARKER);
+            }
+            if options.preserve_paragraphs && is_paragraph_break(gap.as_str()) {
+                replacement = format!("{} {}", replacement, PARAGRAPH_MARKER);
+            }
+            return format!("{} {}", replacement, start_char.as_str());
+        }
+
+        if let Some(heading) = caps.name("heading") {
+            if options.preserve_paragraphs {
+                return format!(" {} ", SECTION_MARKER);
             } else {
-                return format!("{} {}", end_punct.as_str(), start_char.as_str());
+                return format!(" {} ", heading.as_str());
             }
         }
 
-        if let Some(punct) = caps.get(3) {
+        if caps.name("paragraph").is_some() && options.preserve_paragraphs {
+            return format!(" {} ", PARAGRAPH_MARKER);
+        }
+
+        if let (Some(word_end), Some(clitic)) = (caps.name("word_end"), caps.name("clitic")) {
+            return format!("{} {} ", word_end.as_str(), clitic.as_str());
+        }
+
+        if let Some(elision) = caps.name("elision") {
+            return format!(" {} ", elision.as_str());
+        }
+
+        if let Some(word) = caps.name("aphaeresis") {
+            return format!(" {} ", word.as_str());
+        }
+
+        if let Some(punct) = caps.name("punct") {
             if options.preserve_punctuation {
                 return format!(" {} ", punct.as_str());
             } else {
@@ -63,7 +184,12 @@ pub fn tokenize(text: &str, options: &TokenizerOptions) -> Vec<String> {
         }
 
         " ".to_string()
-    });
+    })
+}
+
+fn split_tokens(processed_text: &str, options: &TokenizerOptions) -> Vec<String> {
+    let estimated_token_count = processed_text.len() / 5;
+    let mut tokens = Vec::with_capacity(estimated_token_count);
 
     for token in processed_text.split_whitespace() {
         if !token.is_empty() {
@@ -75,58 +201,95 @@ pub fn tokenize(text: &str, options: &TokenizerOptions) -> Vec<String> {
         }
     }
 
+    if let Some(language) = options.stemming {
+        Stemmer::new(language).stem_tokens(&mut tokens);
+    }
+
     tokens
 }
 
+fn is_paragraph_break(whitespace: &str) -> bool {
+    whitespace.matches('\n').count() >= 2
+}
+
+fn flush_paragraph(current: &mut String, paragraphs: &mut Vec<String>) {
+    if !current.is_empty() {
+        paragraphs.push(std::mem::take(current));
+    }
+}
+
+pub(crate) fn is_heading(line: &str) -> bool {
+    line.starts_with('#') && line.trim_start_matches('#').starts_with([' ', '\t'])
+}
+
+/// Collapses runs of whitespace into single spaces while keeping document structure
+///
+/// Blank lines survive as paragraph breaks ("\n\n") and `#` heading lines
+/// are kept on their own, so that [`tokenize`] can turn them into markers.
 pub fn normalize_text(text: &str) -> String {
-    let mut result = String::with_capacity(text.len());
-    let mut last_was_whitespace = false;
-
-    for c in text.chars() {
-        if c.is_whitespace() {
-            if !last_was_whitespace {
-                result.push(' ');
-                last_was_whitespace = true;
-            }
-        } else {
-            result.push(c);
-            last_was_whitespace = false;
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.is_empty() {
+            flush_paragraph(&mut current, &mut paragraphs);
+            continue;
         }
-    }
 
-    if result.starts_with(' ') {
-        result.remove(0);
-    }
-    if result.ends_with(' ') {
-        result.pop();
+        if is_heading(line) {
+            flush_paragraph(&mut current, &mut paragraphs);
+            paragraphs.push(line.split_whitespace().collect::<Vec<_>>().join(" "));
+            continue;
+        }
+
+        for word in line.split_whitespace() {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
     }
+    flush_paragraph(&mut current, &mut paragraphs);
 
-    result
+    paragraphs.join("\n\n")
 }
 
+/// Tokenizes text over 1 MB in chunks, cut at whitespace so no word or character is split
 pub fn tokenize_large_text(text: &str, options: &TokenizerOptions) -> Result<Vec<String>> {
-    if text.len() > 1_000_000 {
-        const CHUNK_SIZE: usize = 100_000;
-        let mut all_tokens = Vec::with_capacity(text.len() / 5);
-
-        for chunk in text.as_bytes().chunks(CHUNK_SIZE) {
-            let chunk_str = match std::str::from_utf8(chunk) {
-                Ok(s) => s,
-                Err(_) => {
-                    return Err(TextGenError::Tokenization(
-                        "Invalid UTF-8 sequence in text".to_string(),
-                    ));
-                }
-            };
+    const LARGE_TEXT: usize = 1_000_000;
+    const CHUNK_SIZE: usize = 100_000;
 
-            let chunk_tokens = tokenize(chunk_str, options);
-            all_tokens.extend(chunk_tokens);
+    if text.len() <= LARGE_TEXT {
+        return Ok(tokenize(text, options));
+    }
+
+    let mut all_tokens = Vec::with_capacity(text.len() / 5);
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = rest.len();
+        if rest.len() > CHUNK_SIZE {
+            let mut last_boundary = 0;
+            let mut last_space = None;
+            for (index, c) in rest.char_indices() {
+                if index > CHUNK_SIZE {
+                    break;
+                }
+                last_boundary = index;
+                if c.is_whitespace() {
+                    last_space = Some(index + c.len_utf8());
+                }
+            }
+            end = last_space.unwrap_or(last_boundary.max(1));
         }
 
-        Ok(all_tokens)
-    } else {
-        Ok(tokenize(text, options))
+        let (chunk, tail) = rest.split_at(end);
+        all_tokens.extend(tokenize(chunk, options));
+        rest = tail;
     }
+
+    Ok(all_tokens)
 }
 
 #[cfg(test)]
@@ -166,7 +329,7 @@ mod tests {
         };
         let tokens = tokenize(text, &options);
 
-        let expected = vec!["hello", "world!", "<sentence>", "this", "is", "a", "test"];
+        let expected = ["hello", "world!", "<sentence>", "this", "is", "a", "test"];
 
         assert_eq!(
             tokens,
@@ -182,4 +345,37 @@ mod tests {
 
         assert!(tokens.contains(&"<sentence>".to_string()));
     }
+
+    #[test]
+    fn test_tokenize_splits_contractions() {
+        let options = TokenizerOptions::default();
+        let tokens = tokenize("I don't think it's John's", &options);
+
+        let expected = ["i", "do", "n't", "think", "it", "'s", "john", "'s"];
+
+        assert_eq!(tokens, expected.map(String::from));
+    }
+
+    #[test]
+    fn test_tokenize_keeps_elisions_whole() {
+        let options = TokenizerOptions::default();
+        let tokens = tokenize("rock 'n' roll", &options);
+
+        assert_eq!(tokens, ["rock", "'n'", "roll"].map(String::from));
+
+        let tokens = tokenize("'Tis time, get 'em", &options);
+        assert_eq!(
+            tokens,
+            ["'tis", "time", ",", "get", "'em"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn test_is_clitic() {
+        assert!(is_clitic("n't"));
+        assert!(is_clitic("'S"));
+        assert!(is_clitic("’ll"));
+        assert!(!is_clitic("'"));
+        assert!(!is_clitic("'n'"));
+    }
 }
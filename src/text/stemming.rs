@@ -0,0 +1,143 @@
+use super::processing::{is_clitic, is_marker};
+use crate::error::{Result, TextGenError};
+use rust_stemmers::Algorithm;
+use std::fmt;
+
+/// Languages supported by the Snowball stemmers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StemmingLanguage {
+    English,
+    French,
+    Spanish,
+    German,
+    Russian,
+    Italian,
+    Portuguese,
+}
+
+impl StemmingLanguage {
+    /// Parses a language code ("en", "fr", "es", "de", "ru", "it", "pt") or name
+    pub fn from_code(code: &str) -> Result<Self> {
+        match code.to_lowercase().as_str() {
+            "en" | "english" => Ok(Self::English),
+            "fr" | "french" => Ok(Self::French),
+            "es" | "spanish" => Ok(Self::Spanish),
+            "de" | "german" => Ok(Self::German),
+            "ru" | "russian" => Ok(Self::Russian),
+            "it" | "italian" => Ok(Self::Italian),
+            "pt" | "portuguese" => Ok(Self::Portuguese),
+            other => Err(TextGenError::Config(format!(
+                "No stemmer for language '{}'",
+                other
+            ))),
+        }
+    }
+
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Self::English => Algorithm::English,
+            Self::French => Algorithm::French,
+            Self::Spanish => Algorithm::Spanish,
+            Self::German => Algorithm::German,
+            Self::Russian => Algorithm::Russian,
+            Self::Italian => Algorithm::Italian,
+            Self::Portuguese => Algorithm::Portuguese,
+        }
+    }
+}
+
+/// Reduces inflected words to a common stem ("runs", "running" -> "run")
+///
+/// Uses the Snowball algorithms (Porter2 for English), so stemming runs
+/// entirely locally. Stems are always lowercase.
+pub struct Stemmer {
+    language: StemmingLanguage,
+    inner: rust_stemmers::Stemmer,
+}
+
+impl Stemmer {
+    pub fn new(language: StemmingLanguage) -> Self {
+        Self {
+            language,
+            inner: rust_stemmers::Stemmer::create(language.algorithm()),
+        }
+    }
+
+    pub fn language(&self) -> StemmingLanguage {
+        self.language
+    }
+
+    pub fn stem(&self, word: &str) -> String {
+        self.inner.stem(&word.to_lowercase()).into_owned()
+    }
+
+    /// Stems word tokens in place, leaving markers, clitics and punctuation alone
+    ///
+    /// Sentence-final punctuation the tokenizer left attached ("running.") is kept.
+    pub fn stem_tokens(&self, tokens: &mut [String]) {
+        for token in tokens.iter_mut() {
+            if is_word(token) {
+                let word = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+                *token = format!("{}{}", self.stem(word), &token[word.len()..]);
+            }
+        }
+    }
+}
+
+impl Clone for Stemmer {
+    fn clone(&self) -> Self {
+        Self::new(self.language)
+    }
+}
+
+impl fmt::Debug for Stemmer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stemmer")
+            .field("language", &self.language)
+            .finish()
+    }
+}
+
+fn is_word(token: &str) -> bool {
+    !is_marker(token) && !is_clitic(token) && token.chars().any(char::is_alphabetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_stemming() {
+        let stemmer = Stemmer::new(StemmingLanguage::English);
+        assert_eq!(stemmer.stem("run"), "run");
+        assert_eq!(stemmer.stem("runs"), "run");
+        assert_eq!(stemmer.stem("Running"), "run");
+    }
+
+    #[test]
+    fn test_french_stemming() {
+        let stemmer = Stemmer::new(StemmingLanguage::French);
+        assert_eq!(stemmer.stem("continuer"), stemmer.stem("continuait"));
+    }
+
+    #[test]
+    fn test_stem_tokens_skips_markers_and_punctuation() {
+        let stemmer = Stemmer::new(StemmingLanguage::English);
+        let mut tokens: Vec<String> = ["dogs", "do", "n't", "<sentence>", "jumping", "!"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        stemmer.stem_tokens(&mut tokens);
+
+        assert_eq!(tokens, ["dog", "do", "n't", "<sentence>", "jump", "!"]);
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        assert!(StemmingLanguage::from_code("xx").is_err());
+        assert_eq!(
+            StemmingLanguage::from_code("ES").unwrap(),
+            StemmingLanguage::Spanish
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Built-in stopword lists used by [`VocabularyFilter`](super::VocabularyFilter)
+
+const ENGLISH: &[&str] = &[
+    "a",
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "am",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "doing",
+    "down",
+    "during",
+    "each",
+    "few",
+    "for",
+    "from",
+    "further",
+    "had",
+    "has",
+    "have",
+    "having",
+    "he",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "i",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "itself",
+    "just",
+    "me",
+    "more",
+    "most",
+    "my",
+    "myself",
+    "no",
+    "nor",
+    "not",
+    "now",
+    "of",
+    "off",
+    "on",
+    "once",
+    "only",
+    "or",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "she",
+    "should",
+    "so",
+    "some",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "under",
+    "until",
+    "up",
+    "very",
+    "was",
+    "we",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "would",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+const FRENCH: &[&str] = &[
+    "au", "aux", "avec", "ce", "ces", "cette", "dans", "de", "des", "du", "elle", "elles", "en",
+    "est", "et", "eux", "il", "ils", "je", "la", "le", "les", "leur", "leurs", "lui", "ma", "mais",
+    "me", "mes", "moi", "mon", "ne", "nos", "notre", "nous", "on", "ou", "où", "par", "pas",
+    "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sont", "sur", "ta", "te", "tes", "toi",
+    "ton", "tu", "un", "une", "vos", "votre", "vous", "été", "être", "avoir", "fait", "comme",
+    "plus", "tout", "très", "aussi", "était", "ont", "sans", "si",
+];
+
+const SPANISH: &[&str] = &[
+    "a", "al", "algo", "ante", "como", "con", "contra", "cual", "de", "del", "desde", "donde",
+    "el", "ella", "ellas", "ellos", "en", "entre", "era", "es", "esa", "ese", "eso", "esta",
+    "este", "esto", "fue", "ha", "hay", "la", "las", "le", "les", "lo", "los", "me", "mi", "mis",
+    "muy", "más", "ni", "no", "nos", "o", "para", "pero", "por", "que", "qué", "se", "ser", "si",
+    "sin", "sobre", "son", "su", "sus", "también", "te", "tu", "tus", "un", "una", "uno", "unos",
+    "y", "ya", "yo", "él",
+];
+
+const GERMAN: &[&str] = &[
+    "aber", "alle", "als", "also", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "da",
+    "das", "dass", "dem", "den", "der", "des", "die", "doch", "du", "ein", "eine", "einem",
+    "einen", "einer", "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja", "kann", "man",
+    "mit", "nach", "nicht", "noch", "nur", "oder", "sich", "sie", "sind", "so", "um", "und", "uns",
+    "von", "vor", "war", "was", "wenn", "wie", "wir", "wird", "zu", "zum", "zur", "über",
+];
+
+const RUSSIAN: &[&str] = &[
+    "а",
+    "без",
+    "бы",
+    "был",
+    "была",
+    "были",
+    "было",
+    "в",
+    "вам",
+    "вас",
+    "во",
+    "вот",
+    "все",
+    "всё",
+    "вы",
+    "да",
+    "для",
+    "до",
+    "его",
+    "ее",
+    "её",
+    "если",
+    "есть",
+    "еще",
+    "ещё",
+    "же",
+    "за",
+    "и",
+    "из",
+    "или",
+    "им",
+    "их",
+    "к",
+    "как",
+    "когда",
+    "кто",
+    "ли",
+    "мне",
+    "мы",
+    "на",
+    "над",
+    "не",
+    "нет",
+    "ни",
+    "но",
+    "ну",
+    "о",
+    "об",
+    "он",
+    "она",
+    "они",
+    "оно",
+    "от",
+    "по",
+    "под",
+    "при",
+    "с",
+    "со",
+    "так",
+    "там",
+    "то",
+    "тоже",
+    "только",
+    "ты",
+    "у",
+    "уж",
+    "что",
+    "чтобы",
+    "это",
+    "я",
+];
+
+/// Returns the built-in stopword list for a language code, if there is one
+pub fn builtin(language: &str) -> Option<&'static [&'static str]> {
+    match language.to_lowercase().as_str() {
+        "en" | "english" => Some(ENGLISH),
+        "fr" | "french" => Some(FRENCH),
+        "es" | "spanish" => Some(SPANISH),
+        "de" | "german" => Some(GERMAN),
+        "ru" | "russian" => Some(RUSSIAN),
+        _ => None,
+    }
+}
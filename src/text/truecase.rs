@@ -0,0 +1,111 @@
+use super::processing::is_marker;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Remembers how each token is usually written so lowercased output can be
+/// restored to its natural casing ("paris" -> "Paris", "i" -> "I")
+///
+/// Sentence-initial words are not counted, since their capital letter says
+/// nothing about how the word is normally written.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TrueCaser {
+    forms: HashMap<String, HashMap<String, usize>>,
+}
+
+impl TrueCaser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the surface forms of tokens produced without lowercasing
+    pub fn observe(&mut self, tokens: &[String]) {
+        let mut sentence_start = true;
+
+        for token in tokens {
+            if is_marker(token) || token == "#" || is_terminator(token) {
+                sentence_start = true;
+                continue;
+            }
+
+            if !token.chars().any(char::is_alphabetic) {
+                continue;
+            }
+
+            if !sentence_start {
+                *self
+                    .forms
+                    .entry(token.to_lowercase())
+                    .or_default()
+                    .entry(token.clone())
+                    .or_insert(0) += 1;
+            }
+
+            sentence_start = token.ends_with(['.', '!', '?']);
+        }
+    }
+
+    /// Returns the most frequent observed casing of `token`, or the token itself
+    pub fn restore(&self, token: &str) -> String {
+        let Some(forms) = self.forms.get(&token.to_lowercase()) else {
+            return token.to_string();
+        };
+
+        forms
+            .iter()
+            // Prefer the lowercase form on ties, then the alphabetically first one
+            .max_by_key(|(form, count)| {
+                (
+                    **count,
+                    !form.chars().any(char::is_uppercase),
+                    Reverse(form.as_str()),
+                )
+            })
+            .map(|(form, _)| form.clone())
+            .unwrap_or_else(|| token.to_string())
+    }
+
+    pub fn apply(&self, tokens: &[String]) -> Vec<String> {
+        tokens.iter().map(|token| self.restore(token)).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forms.is_empty()
+    }
+}
+
+fn is_terminator(token: &str) -> bool {
+    matches!(token, "." | "!" | "?")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_restore_most_frequent_form() {
+        let mut caser = TrueCaser::new();
+        caser.observe(&tokens(
+            "We saw Paris and I liked Paris . The end , said paris",
+        ));
+
+        assert_eq!(caser.restore("paris"), "Paris");
+        assert_eq!(caser.restore("i"), "I");
+        assert_eq!(caser.restore("unknown"), "unknown");
+    }
+
+    #[test]
+    fn test_sentence_initial_words_are_ignored() {
+        let mut caser = TrueCaser::new();
+        caser.observe(&tokens(
+            "The cat . The dog <SENTENCE> The bird saw the fish",
+        ));
+
+        assert_eq!(caser.restore("the"), "the");
+        assert_eq!(caser.restore("cat"), "cat");
+    }
+}
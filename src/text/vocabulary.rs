@@ -0,0 +1,226 @@
+use super::stemming::Stemmer;
+use super::stopwords;
+use crate::error::{Result, TextGenError};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_MIN_LENGTH: usize = 3;
+
+static WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\w+").unwrap());
+
+/// Splits text into words, the unit counted by insights and the wordcloud
+pub fn words(text: &str) -> impl Iterator<Item = &str> {
+    WORD.find_iter(text).map(|m| m.as_str())
+}
+
+/// Decides which words are worth reporting in word frequency views
+///
+/// Words are compared in lowercase. By default only words shorter than three
+/// characters are dropped; stopword lists, length limits and an exclusion
+/// pattern can be added on top.
+#[derive(Clone, Debug)]
+pub struct VocabularyFilter {
+    stopwords: HashSet<String>,
+    min_length: usize,
+    max_length: Option<usize>,
+    exclude: Option<Regex>,
+    stemmer: Option<Stemmer>,
+}
+
+impl Default for VocabularyFilter {
+    fn default() -> Self {
+        Self {
+            stopwords: HashSet::new(),
+            min_length: DEFAULT_MIN_LENGTH,
+            max_length: None,
+            exclude: None,
+            stemmer: None,
+        }
+    }
+}
+
+impl VocabularyFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the built-in stopword list for a language code ("en", "fr", "es", "de", "ru")
+    pub fn with_stopwords(mut self, language: &str) -> Result<Self> {
+        let list = stopwords::builtin(language).ok_or_else(|| {
+            TextGenError::Config(format!("No stopword list for language '{}'", language))
+        })?;
+        self.stopwords.extend(list.iter().map(|w| w.to_string()));
+        Ok(self)
+    }
+
+    /// Adds stopwords from a file with one word per line; `#` starts a comment
+    pub fn with_stopword_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref()).map_err(|e| {
+            TextGenError::Config(format!(
+                "Failed to read stopword file {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+
+        self.stopwords.extend(
+            content
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default().trim())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase),
+        );
+        Ok(self)
+    }
+
+    pub fn with_stopword_list<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.stopwords
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Drops words matching `pattern` (e.g. "^\d+$" to ignore numbers)
+    pub fn with_exclude_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            TextGenError::Config(format!("Invalid exclude pattern '{}': {}", pattern, e))
+        })?;
+        self.exclude = Some(regex);
+        Ok(self)
+    }
+
+    /// Counts inflections of a word together ("run", "runs", "running")
+    ///
+    /// Each group is reported under its most frequent form.
+    pub fn with_stemmer(mut self, stemmer: Stemmer) -> Self {
+        self.stemmer = Some(stemmer);
+        self
+    }
+
+    /// Returns true if the (lowercased) word should be counted
+    pub fn accepts(&self, word: &str) -> bool {
+        let length = word.chars().count();
+
+        length >= self.min_length
+            && self.max_length.is_none_or(|max| length <= max)
+            && !self.stopwords.contains(word)
+            && !self.exclude.as_ref().is_some_and(|re| re.is_match(word))
+    }
+
+    /// Counts accepted words in `text`, lowercased
+    pub fn count_words(&self, text: &str) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for word in words(text) {
+            let word = word.to_lowercase();
+            if self.accepts(&word) {
+                *counts.entry(word).or_insert(0) += 1;
+            }
+        }
+
+        match &self.stemmer {
+            Some(stemmer) => group_by_stem(counts, stemmer),
+            None => counts,
+        }
+    }
+
+    /// Accepted words sorted by descending frequency, ties broken alphabetically
+    pub fn most_common(&self, text: &str, limit: usize) -> Vec<(String, usize)> {
+        let mut frequencies: Vec<(String, usize)> = self.count_words(text).into_iter().collect();
+        frequencies.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        frequencies.truncate(limit);
+        frequencies
+    }
+}
+
+/// Merges word counts sharing a stem, keyed by the group's most frequent form
+fn group_by_stem(counts: HashMap<String, usize>, stemmer: &Stemmer) -> HashMap<String, usize> {
+    let mut groups: HashMap<String, (String, usize, usize)> = HashMap::new();
+
+    for (word, count) in counts {
+        let (form, form_count, total) = groups
+            .entry(stemmer.stem(&word))
+            .or_insert_with(|| (word.clone(), 0, 0));
+        if count > *form_count || (count == *form_count && word < *form) {
+            *form = word;
+            *form_count = count;
+        }
+        *total += count;
+    }
+
+    groups
+        .into_values()
+        .map(|(form, _, total)| (form, total))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::stemming::StemmingLanguage;
+
+    #[test]
+    fn test_default_filter_drops_short_words() {
+        let filter = VocabularyFilter::default();
+        assert!(!filter.accepts("an"));
+        assert!(filter.accepts("the"));
+    }
+
+    #[test]
+    fn test_builtin_stopwords() {
+        let filter = VocabularyFilter::new()
+            .with_stopwords("en")
+            .unwrap()
+            .with_stopwords("ru")
+            .unwrap();
+
+        assert!(!filter.accepts("the"));
+        assert!(!filter.accepts("только"));
+        assert!(filter.accepts("river"));
+        assert!(VocabularyFilter::new().with_stopwords("xx").is_err());
+    }
+
+    #[test]
+    fn test_length_and_pattern_filters() {
+        let filter = VocabularyFilter::new()
+            .with_min_length(2)
+            .with_max_length(5)
+            .with_exclude_pattern(r"^\d+$")
+            .unwrap();
+
+        assert!(filter.accepts("ok"));
+        assert!(!filter.accepts("lengthy"));
+        assert!(!filter.accepts("2024"));
+    }
+
+    #[test]
+    fn test_most_common() {
+        let filter = VocabularyFilter::new().with_stopword_list(["the"]);
+        let common = filter.most_common("The cat and the dog. The cat!", 2);
+
+        assert_eq!(common, vec![("cat".to_string(), 2), ("and".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_stemmer_groups_word_forms() {
+        let filter = VocabularyFilter::new().with_stemmer(Stemmer::new(StemmingLanguage::English));
+        let common = filter.most_common("Running runs. She runs and will run again.", 1);
+
+        assert_eq!(common, vec![("runs".to_string(), 4)]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Local HTTP server shared by the integration tests
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A canned response: status, headers and body
+#[derive(Clone, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    /// A 200 response with `body`
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::status(200).with_body(body)
+    }
+
+    /// An empty response with `status`
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+/// Serves canned replies by path on a local port until the test process exits
+///
+/// A path listed several times answers with each of its replies in turn and
+/// then keeps repeating the last one. Paths with a query string match the
+/// full target first, then the path alone. Anything else gets a 404.
+pub struct TestServer {
+    /// `http://127.0.0.1:<port>`, without a trailing slash
+    pub base: String,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl TestServer {
+    pub fn start(routes: Vec<(&'static str, Reply)>) -> Self {
+        Self::start_with(|_| routes)
+    }
+
+    /// Builds the routes from the server's base URL, for replies that link back to it
+    pub fn start_with(routes: impl FnOnce(&str) -> Vec<(&'static str, Reply)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        let mut replies: HashMap<&str, VecDeque<Reply>> = HashMap::new();
+        for (path, reply) in routes(&base) {
+            replies.entry(path).or_default().push_back(reply);
+        }
+        let hits = Arc::new(Mutex::new(HashMap::new()));
+
+        let served = hits.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                    line.clear();
+                }
+
+                let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let path = target.split('?').next().unwrap_or(target);
+                let key = [target, path]
+                    .into_iter()
+                    .find(|key| replies.contains_key(key));
+                *served
+                    .lock()
+                    .unwrap()
+                    .entry(target.to_string())
+                    .or_insert(0) += 1;
+
+                let reply = match key.and_then(|key| replies.get_mut(key)) {
+                    Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+                    Some(queue) => queue[0].clone(),
+                    None => Reply::status(404),
+                };
+                let reason = http::StatusCode::from_u16(reply.status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or_default();
+
+                write!(stream, "HTTP/1.1 {} {}\r\n", reply.status, reason).unwrap();
+                for (name, value) in &reply.headers {
+                    write!(stream, "{}: {}\r\n", name, value).unwrap();
+                }
+                write!(
+                    stream,
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    reply.body.len()
+                )
+                .unwrap();
+                stream.write_all(&reply.body).unwrap();
+            }
+        });
+
+        Self { base, hits }
+    }
+
+    /// Absolute URL of `path` on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    /// Number of requests received for `target` (path and query)
+    pub fn hits(&self, target: &str) -> usize {
+        self.hits.lock().unwrap().get(target).copied().unwrap_or(0)
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_literal_unwrap)]
+
 use std::io;
 use text_gen_ngram::error::{Result, TextGenError};
 
@@ -0,0 +1,170 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{Reply, TestServer};
+use serde_json::json;
+use text_gen_ngram::scrapers::providers::feed::{FeedScraper, parse_feed};
+use text_gen_ngram::scrapers::{Scraper, ScraperConfig, load_scraper_from_config};
+
+const RSS: &str = include_str!("fixtures/feed_rss.xml");
+const ATOM: &str = include_str!("fixtures/feed_atom.xml");
+
+#[test]
+fn test_parse_rss() {
+    let feed = parse_feed(RSS).unwrap();
+
+    assert_eq!(feed.title.as_deref(), Some("Harbour Town Gazette"));
+    assert_eq!(feed.language.as_deref(), Some("en"));
+    assert_eq!(feed.entries.len(), 3);
+
+    let ferry = &feed.entries[0];
+    assert_eq!(ferry.title.as_deref(), Some("Ferry timetable changes"));
+    assert_eq!(
+        ferry.link.as_deref(),
+        Some("https://news.example.com/ferry")
+    );
+    assert_eq!(ferry.author.as_deref(), Some("Ada Lovelace"));
+    assert_eq!(
+        ferry.published,
+        Some(Utc.with_ymd_and_hms(2026, 10, 5, 8, 30, 0).unwrap())
+    );
+    // content:encoded wins over the description, and HTML is stripped
+    assert_eq!(
+        ferry.content,
+        "The morning ferry now leaves at seven.\n\nEvening sailings are unchanged."
+    );
+
+    let market = &feed.entries[1];
+    assert_eq!(
+        market.link.as_deref(),
+        Some("https://news.example.com/market")
+    );
+    assert_eq!(market.content, "Stalls open at nine & close at four.");
+
+    // A guid that is not a URL is not a link
+    assert_eq!(feed.entries[2].link, None);
+    assert_eq!(feed.entries[2].content, "Plain text only.");
+}
+
+#[test]
+fn test_parse_atom() {
+    let feed = parse_feed(ATOM).unwrap();
+
+    assert_eq!(feed.title.as_deref(), Some("Saaristoblogi"));
+    assert_eq!(feed.language.as_deref(), Some("fi"));
+
+    let spring = &feed.entries[0];
+    assert_eq!(spring.title.as_deref(), Some("Kevät & kesä"));
+    assert_eq!(
+        spring.link.as_deref(),
+        Some("https://blog.example.fi/kevat")
+    );
+    assert_eq!(spring.author.as_deref(), Some("Tove Jansson"));
+    assert_eq!(
+        spring.published,
+        Some(Utc.with_ymd_and_hms(2026, 10, 6, 15, 30, 2).unwrap())
+    );
+    assert_eq!(spring.content, "Jäät lähtivät.\n\nLinnut palasivat.");
+
+    let autumn = &feed.entries[1];
+    assert_eq!(
+        autumn.link.as_deref(),
+        Some("https://blog.example.fi/syksy")
+    );
+    assert_eq!(autumn.content, "Lehdet putoavat.");
+    assert!(autumn.published.is_some());
+}
+
+#[test]
+fn test_parse_rejects_other_documents() {
+    assert!(parse_feed("<html><body>Moved</body></html>").is_err());
+    assert!(parse_feed("<rss version=\"2.0\"></rss>").is_err());
+    assert!(parse_feed("not a feed").is_err());
+}
+
+/// Serves `/rss.xml` (the RSS fixture pointing at this server) and the ferry article
+fn serve_feed() -> String {
+    let server = TestServer::start_with(|base| {
+        vec![
+            (
+                "/rss.xml",
+                Reply::ok(RSS.replace("https://news.example.com", base)),
+            ),
+            (
+                "/ferry",
+                Reply::ok(
+                    "<html><body><nav>Menu</nav><article><h1>Ferry</h1><p>Full story about the \
+                     new timetable.</p></article><footer>Ads</footer></body></html>",
+                ),
+            ),
+        ]
+    });
+
+    server.base
+}
+
+#[tokio::test]
+async fn test_feed_scraper_uses_feed_content() {
+    let base = serve_feed();
+    let scraper = FeedScraper::new(vec![
+        format!("{}/rss.xml", base),
+        // The same feed twice yields each entry once
+        format!("{}/rss.xml", base),
+        format!("{}/missing.xml", base),
+    ])
+    .with_max_entries(2);
+
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    assert_eq!(documents.len(), 2);
+    assert_eq!(
+        documents[0].title.as_deref(),
+        Some("Ferry timetable changes")
+    );
+    assert_eq!(documents[0].author.as_deref(), Some("Ada Lovelace"));
+    assert_eq!(documents[0].language.as_deref(), Some("en"));
+    assert_eq!(
+        documents[0].source.as_deref(),
+        Some(format!("{}/ferry", base).as_str())
+    );
+    assert!(documents[0].text.starts_with("The morning ferry"));
+    assert_eq!(documents[1].text, "Stalls open at nine & close at four.");
+}
+
+#[tokio::test]
+async fn test_feed_scraper_follows_links_from_config() {
+    let base = serve_feed();
+    let config = ScraperConfig {
+        scraper_type: "feed".to_string(),
+        settings: json!({
+            "feeds": [format!("{}/rss.xml", base)],
+            "selector": "article",
+        }),
+    };
+
+    let scraper = load_scraper_from_config(&config).unwrap();
+    let texts: Vec<String> = scraper
+        .fetch_documents()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|document| document.text)
+        .collect();
+
+    // The market page is missing, so its entry keeps the feed content
+    assert_eq!(
+        texts,
+        [
+            "# Ferry\n\nFull story about the new timetable.",
+            "Stalls open at nine & close at four.",
+            "Plain text only."
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_feed_scraper_fails_without_feeds() {
+    let scraper = FeedScraper::new(Vec::new());
+
+    assert!(scraper.fetch_text().await.is_err());
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::{Reply, TestServer};
+use std::path::PathBuf;
+use text_gen_ngram::scrapers::providers::gutenberg::{
+    Chapter, GutenbergScraper, GutenbergSource, parse_book,
+};
+use text_gen_ngram::scrapers::{Scraper, ScraperConfig, load_scraper_from_config};
+
+const BOOK: &str = include_str!("fixtures/gutenberg_book.txt");
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gutenberg_book.txt")
+}
+
+#[test]
+fn test_parse_book_strips_boilerplate() {
+    let book = parse_book(BOOK);
+
+    assert_eq!(book.title.as_deref(), Some("The Lighthouse Keeper"));
+    assert_eq!(book.author.as_deref(), Some("Mary Example"));
+    assert_eq!(book.language.as_deref(), Some("en"));
+
+    let headings: Vec<Option<&str>> = book
+        .chapters
+        .iter()
+        .map(|chapter| chapter.heading.as_deref())
+        .collect();
+    assert_eq!(
+        headings,
+        [
+            None,
+            Some("CHAPTER I. THE STORM"),
+            Some("CHAPTER II. The Visitor")
+        ]
+    );
+
+    // Only the preface survives from the front matter
+    assert!(
+        book.chapters[0]
+            .text
+            .starts_with("This little story was written")
+    );
+    assert!(!book.chapters[0].text.contains('\n'));
+
+    assert_eq!(
+        book.chapters[1].text,
+        "The wind rose in the evening and the keeper climbed the stairs to light the lamp. \
+         Far below, the waves broke white against the rocks, and the gulls had gone quiet.\n\n\
+         He trimmed the wick and watched the beam sweep across the water."
+    );
+    assert!(
+        book.chapters[2]
+            .text
+            .ends_with("The keeper read the letter twice and smiled.")
+    );
+
+    let all_text: String = book
+        .chapters
+        .iter()
+        .map(|chapter| chapter.text.as_str())
+        .collect();
+    for boilerplate in [
+        "Project Gutenberg",
+        "Produced by",
+        "CONTENTS",
+        "Illustration",
+        "Transcriber",
+        "typographical",
+        "LICENSE",
+    ] {
+        assert!(!all_text.contains(boilerplate), "found {:?}", boilerplate);
+    }
+}
+
+#[test]
+fn test_parse_book_without_markers() {
+    let book = parse_book(
+        "Chapter 1\n\nIt was a bright cold day in April, and the clocks were striking \
+         thirteen as the story of this very short example began.",
+    );
+
+    assert_eq!(book.title, None);
+    assert_eq!(
+        book.chapters,
+        [Chapter {
+            heading: Some("Chapter 1".to_string()),
+            text: "It was a bright cold day in April, and the clocks were striking thirteen \
+                   as the story of this very short example began."
+                .to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_source_from_value() {
+    assert_eq!(
+        GutenbergSource::from_value(&serde_json::json!(1342)),
+        Some(GutenbergSource::Book(1342))
+    );
+    assert_eq!(
+        GutenbergSource::from_value(&serde_json::json!("84")),
+        Some(GutenbergSource::Book(84))
+    );
+    assert_eq!(
+        GutenbergSource::from_value(&serde_json::json!("books/pg84.txt")),
+        Some(GutenbergSource::File(PathBuf::from("books/pg84.txt")))
+    );
+    assert_eq!(GutenbergSource::from_value(&serde_json::json!("")), None);
+}
+
+#[tokio::test]
+async fn test_local_file_is_read_offline() {
+    let config = ScraperConfig {
+        scraper_type: "gutenberg".to_string(),
+        settings: serde_json::json!({
+            "file": fixture_path(),
+            // An unreachable mirror: local files must never touch the network
+            "mirror": "http://127.0.0.1:9",
+        }),
+    };
+
+    let documents = load_scraper_from_config(&config)
+        .unwrap()
+        .fetch_documents()
+        .await
+        .unwrap();
+
+    assert_eq!(documents.len(), 3);
+    assert_eq!(
+        documents[1].title.as_deref(),
+        Some("The Lighthouse Keeper: CHAPTER I. THE STORM")
+    );
+    assert_eq!(documents[1].author.as_deref(), Some("Mary Example"));
+    assert_eq!(documents[1].language.as_deref(), Some("en"));
+    assert_eq!(
+        documents[1].source.as_deref(),
+        Some(fixture_path().display().to_string().as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_whole_book_as_one_document() {
+    let scraper = GutenbergScraper::new(vec![GutenbergSource::File(fixture_path())])
+        .with_split_chapters(false);
+
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].title.as_deref(), Some("The Lighthouse Keeper"));
+    assert!(
+        documents[0]
+            .text
+            .contains("\n\nCHAPTER II. The Visitor\n\nIn the morning")
+    );
+}
+
+/// Serves the fixture as book 99999 of a mirror
+fn serve_mirror() -> String {
+    let server = TestServer::start(vec![("/cache/epub/99999/pg99999.txt", Reply::ok(BOOK))]);
+
+    server.url("/")
+}
+
+#[tokio::test]
+async fn test_book_id_is_fetched_from_mirror() {
+    let mirror = serve_mirror();
+    let scraper =
+        GutenbergScraper::new(vec![GutenbergSource::Book(99999), GutenbergSource::Book(1)])
+            .with_mirror(&mirror);
+
+    assert_eq!(
+        scraper.book_url(1),
+        format!("{}cache/epub/1/pg1.txt", mirror)
+    );
+
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    // Book 1 is missing from the mirror and skipped
+    assert_eq!(documents.len(), 3);
+    assert_eq!(
+        documents[0].source.as_deref(),
+        Some(format!("{}cache/epub/99999/pg99999.txt", mirror).as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_fails_without_books() {
+    let scraper = GutenbergScraper::new(Vec::new());
+
+    assert!(scraper.fetch_text().await.is_err());
+}
@@ -1,5 +1,18 @@
+mod common;
+
+use common::{Reply, TestServer};
 use reqwest::Client;
-use text_gen_ngram::scrapers::HTTP_CLIENT;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use text_gen_ngram::scrapers::{HTTP_CLIENT, RequestClient, RequestPolicy, ResponseCache};
+
+fn fast_policy() -> RequestPolicy {
+    RequestPolicy {
+        base_delay: Duration::from_millis(1),
+        ..RequestPolicy::default()
+    }
+}
 
 #[test]
 fn test_http_client_initialization() {
@@ -34,3 +47,196 @@ fn test_client_builder_defaults() {
     let client = client.unwrap();
     assert!(client.get("https://example.com").build().is_ok());
 }
+
+#[tokio::test]
+async fn test_request_client_retries_transient_errors() {
+    let server = TestServer::start(vec![
+        ("/", Reply::status(429).with_header("Retry-After", "0")),
+        ("/", Reply::status(503)),
+        ("/", Reply::ok("ok")),
+    ]);
+    let client = RequestClient::new(fast_policy());
+
+    let response = client.send(HTTP_CLIENT.get(server.url("/"))).await.unwrap();
+
+    assert!(response.status().is_success());
+    assert_eq!(server.hits("/"), 3);
+    assert_eq!(
+        client.retries_left(),
+        RequestPolicy::default().retry_budget - 2
+    );
+}
+
+#[tokio::test]
+async fn test_request_client_stops_when_budget_is_spent() {
+    let server = TestServer::start(vec![
+        ("/", Reply::status(503)),
+        ("/", Reply::status(503)),
+        ("/", Reply::ok("ok")),
+    ]);
+    let client = RequestClient::new(RequestPolicy {
+        retry_budget: 1,
+        ..fast_policy()
+    });
+
+    let response = client.send(HTTP_CLIENT.get(server.url("/"))).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(server.hits("/"), 2);
+    assert_eq!(client.retries_left(), 0);
+}
+
+#[tokio::test]
+async fn test_request_client_gives_up_on_long_retry_after() {
+    let server = TestServer::start(vec![
+        ("/", Reply::status(503).with_header("Retry-After", "120")),
+        ("/", Reply::ok("ok")),
+    ]);
+    let client = RequestClient::new(RequestPolicy {
+        max_delay: Duration::from_secs(5),
+        ..fast_policy()
+    });
+
+    let started = std::time::Instant::now();
+    let response = client.send(HTTP_CLIENT.get(server.url("/"))).await.unwrap();
+
+    // Retrying sooner than the server asked would only be refused again
+    assert_eq!(response.status().as_u16(), 503);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(client.retries_left(), RequestPolicy::default().retry_budget);
+}
+
+#[tokio::test]
+async fn test_rate_limited_host_does_not_block_other_hosts() {
+    let server = TestServer::start(vec![("/", Reply::ok("ok"))]);
+    let url = server.url("/");
+    let other_host = url.replace("127.0.0.1", "localhost");
+    let client = RequestClient::new(RequestPolicy {
+        max_concurrent: 1,
+        min_interval: Duration::from_secs(2),
+        ..fast_policy()
+    });
+    client.send(HTTP_CLIENT.get(&url)).await.unwrap();
+
+    // The second request to the first host waits for its slot without holding the only permit
+    let started = std::time::Instant::now();
+    let (delayed, other) = tokio::join!(client.send(HTTP_CLIENT.get(&url)), async {
+        let response = client.send(HTTP_CLIENT.get(&other_host)).await;
+        (response, started.elapsed())
+    });
+
+    assert!(delayed.unwrap().status().is_success());
+    let (response, elapsed) = other;
+    assert!(response.unwrap().status().is_success());
+    assert!(elapsed < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_request_client_does_not_retry_client_errors() {
+    let server = TestServer::start(vec![("/", Reply::status(404)), ("/", Reply::ok("ok"))]);
+    let client = RequestClient::new(fast_policy());
+
+    let response = client.send(HTTP_CLIENT.get(server.url("/"))).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(server.hits("/"), 1);
+    assert_eq!(client.retries_left(), RequestPolicy::default().retry_budget);
+}
+
+fn temp_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("text-gen-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn cached_client(cache: ResponseCache) -> RequestClient {
+    RequestClient::new(RequestPolicy {
+        max_retries: 0,
+        ..fast_policy()
+    })
+    .with_cache(Some(Arc::new(cache)))
+}
+
+#[tokio::test]
+async fn test_cache_serves_fresh_responses() {
+    let dir = temp_cache_dir("fresh");
+    let server = TestServer::start(vec![("/", Reply::ok("ok"))]);
+    let client = cached_client(ResponseCache::new(&dir));
+
+    for _ in 0..3 {
+        let response = client.send(HTTP_CLIENT.get(server.url("/"))).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+    // Only the first request reaches the server
+    assert_eq!(server.hits("/"), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_cache_revalidates_stale_responses() {
+    let dir = temp_cache_dir("stale");
+    let server = TestServer::start(vec![
+        ("/", Reply::ok("ok").with_header("ETag", "\"v1\"")),
+        ("/", Reply::status(304).with_header("ETag", "\"v1\"")),
+    ]);
+    let url = server.url("/");
+    let client = cached_client(ResponseCache::new(&dir).with_ttl(Duration::ZERO));
+
+    let first = client.send(HTTP_CLIENT.get(&url)).await.unwrap();
+    assert_eq!(first.text().await.unwrap(), "ok");
+
+    let second = client.send(HTTP_CLIENT.get(&url)).await.unwrap();
+    assert!(second.status().is_success());
+    assert_eq!(second.headers()["etag"], "\"v1\"");
+    assert_eq!(second.text().await.unwrap(), "ok");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_offline_mode_only_uses_cache() {
+    let dir = temp_cache_dir("offline");
+    let server = TestServer::start(vec![("/", Reply::ok("ok"))]);
+    let url = server.url("/");
+    let offline = cached_client(
+        ResponseCache::new(&dir)
+            .with_ttl(Duration::ZERO)
+            .with_offline(true),
+    );
+
+    let missing = offline.send(HTTP_CLIENT.get(&url)).await;
+    assert!(missing.unwrap_err().to_string().contains("offline"));
+
+    let online = cached_client(ResponseCache::new(&dir));
+    online.send(HTTP_CLIENT.get(&url)).await.unwrap();
+
+    // Served from the cache even though it is stale
+    let response = offline.send(HTTP_CLIENT.get(&url)).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+    assert_eq!(server.hits("/"), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_cached_response_keeps_redirected_url() {
+    let dir = temp_cache_dir("redirect");
+    let server = TestServer::start(vec![
+        ("/old", Reply::status(301).with_header("Location", "/new")),
+        ("/new", Reply::ok("moved")),
+    ]);
+    let client = cached_client(ResponseCache::new(&dir));
+
+    for _ in 0..2 {
+        let response = client
+            .send(HTTP_CLIENT.get(server.url("/old")))
+            .await
+            .unwrap();
+        assert_eq!(response.url().as_str(), server.url("/new"));
+        assert_eq!(response.text().await.unwrap(), "moved");
+    }
+    assert_eq!(server.hits("/old"), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
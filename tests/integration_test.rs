@@ -23,6 +23,40 @@ fn test_end_to_end_generation() {
     assert!(!generated.is_empty());
 }
 
+#[test]
+fn test_truecasing_restores_proper_nouns() {
+    let text = "We flew to Paris in May. Then i think I saw Paris again. \
+                My friend from Paris said I should stay.";
+
+    let model = Trainer::new(2).train_from_text(text).unwrap();
+
+    assert!(model.model.keys().flatten().all(|t| *t == t.to_lowercase()));
+    assert_eq!(model.casing.restore("paris"), "Paris");
+    assert_eq!(model.casing.restore("i"), "I");
+    assert_eq!(model.casing.restore("flew"), "flew");
+
+    let generator = Generator::new(&model);
+    let generated = generator.generate(Some(vec!["from".to_string(), "paris".to_string()]), 5);
+    assert!(generated.starts_with("From Paris"));
+}
+
+#[test]
+fn test_documents_do_not_share_ngrams() {
+    let options = TokenizerOptions {
+        preserve_punctuation: false,
+        preserve_sentence_boundaries: false,
+        ..Default::default()
+    };
+    let trainer = Trainer::new(1).with_tokenizer_options(options);
+    let model = trainer
+        .train_from_documents(&["alpha beta", "gamma delta"])
+        .unwrap();
+
+    assert!(!model.model.contains_key(&vec!["beta".to_string()]));
+    assert_eq!(model.model[&vec!["gamma".to_string()]], vec!["delta"]);
+    assert_eq!(model.get_stats().total_tokens, 2);
+}
+
 #[test]
 fn test_model_optimization() {
     let mut model = NGramModel::new(1);
@@ -64,6 +98,8 @@ fn test_tokenization_options() {
         lowercase: false,
         preserve_punctuation: false,
         preserve_sentence_boundaries: false,
+        preserve_paragraphs: false,
+        stemming: None,
     };
     let custom_tokens = tokenize(text, &custom_options);
 
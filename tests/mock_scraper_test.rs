@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use text_gen_ngram::error::{Result, TextGenError};
 use text_gen_ngram::model::{Generator, Trainer};
-use text_gen_ngram::scrapers::Scraper;
+use text_gen_ngram::scrapers::{Document, Scraper, join_documents};
+use text_gen_ngram::text::TokenizerOptions;
 
 // A mock scraper that returns predefined text
 struct MockScraper {
@@ -84,3 +85,54 @@ async fn test_end_to_end_with_mock() {
     assert!(generated.starts_with("The fox"));
     assert!(!generated.is_empty());
 }
+
+// A mock scraper that returns several songs as separate documents
+struct SongsScraper;
+
+#[async_trait]
+impl Scraper for SongsScraper {
+    async fn fetch_text(&self) -> Result<String> {
+        Ok(join_documents(&self.fetch_documents().await?))
+    }
+
+    async fn fetch_documents(&self) -> Result<Vec<Document>> {
+        Ok(vec![
+            Document::new("la la land")
+                .with_title("First")
+                .with_author("Band"),
+            Document::new("land ho").with_title("Second"),
+        ])
+    }
+}
+
+#[tokio::test]
+async fn test_default_fetch_documents_wraps_text() {
+    let scraper = MockScraper::new("Just one text.");
+
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].text, "Just one text.");
+    assert_eq!(documents[0].title, None);
+}
+
+#[tokio::test]
+async fn test_documents_keep_metadata_and_boundaries() {
+    let scraper = SongsScraper;
+
+    let text = scraper.fetch_text().await.unwrap();
+    assert_eq!(text, "# Band - First\n\nla la land\n\n# Second\n\nland ho");
+
+    // "land" ends the first song, so only the second song may follow it
+    let model = Trainer::new(1)
+        .with_tokenizer_options(TokenizerOptions {
+            preserve_punctuation: false,
+            preserve_sentence_boundaries: false,
+            preserve_paragraphs: false,
+            ..TokenizerOptions::default()
+        })
+        .train(&scraper)
+        .await
+        .unwrap();
+    assert_eq!(model.model[&vec!["land".to_string()]], vec!["ho"]);
+}
@@ -1,6 +1,11 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{Reply, TestServer};
 use serde_json::json;
+use std::time::{Duration, Instant};
 use text_gen_ngram::scrapers::{
-    Scraper, ScraperConfig, load_scraper_from_config,
+    RequestPolicy, Scraper, ScraperConfig, load_scraper_from_config,
     providers::{BasicScraper, WikipediaScraper},
     scraper_trait::ScraperExt,
 };
@@ -68,10 +73,304 @@ fn test_basic_scraper_from_config() {
         "url": "https://example.com",
         "selector": "body"
     });
-    let _scraper = BasicScraper::from_config(&settings);
+    let _scraper = BasicScraper::from_config(&settings).unwrap();
 
     let minimal_settings = json!({});
-    let scraper = BasicScraper::from_config(&minimal_settings);
+    let scraper = BasicScraper::from_config(&minimal_settings).unwrap();
 
     assert!(!scraper.is_null());
 }
+
+#[test]
+fn test_basic_scraper_rejects_invalid_filters() {
+    for (key, value, message) in [
+        ("link_pattern", "/(a", "Invalid link pattern"),
+        ("modified_since", "last week", "Invalid modified_since"),
+    ] {
+        let mut settings = json!({ "url": "https://example.com" });
+        settings[key] = json!(value);
+
+        let Err(error) = BasicScraper::from_config(&settings) else {
+            panic!("{} '{}' was accepted", key, value);
+        };
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+}
+
+/// Serves a small site: `/` links to `/a`, `/b` and `/skip/c`; `/a` links on to `/a2`
+///
+/// `/robots.txt` returns `robots`, or 404 when it is `None`. `/sitemap.xml` is
+/// an index of `/pages.xml`, `/old.xml` and a missing sitemap.
+fn serve_site(robots: Option<&'static str>) -> String {
+    let server = TestServer::start_with(|base| {
+        let page = |body: &str| Reply::ok(body).with_header("Content-Type", "text/html");
+        let sitemap = |path| {
+            Reply::ok(site_sitemap(path, base)).with_header("Content-Type", "application/xml")
+        };
+
+        let mut routes = vec![
+            (
+                "/",
+                page(
+                    "<html><head><title>Home</title></head><body><nav>Menu</nav>\
+                     <main>Home page text.</main>\
+                     <a href=\"/a\">A</a> <a href=\"b#top\">B</a> <a href=\"/a\">A again</a>\
+                     <a href=\"/skip/c\">C</a> <a href=\"https://example.com/x\">Elsewhere</a>\
+                     <a href=\"mailto:me@example.com\">Mail</a></body></html>",
+                ),
+            ),
+            (
+                "/a",
+                page("<main>Page A text.</main><a href=\"/\">Home</a><a href=\"/a2\">A2</a>"),
+            ),
+            ("/a2", page("<main>Page A2 text.</main>")),
+            ("/b", page("<main>Page B text.</main>")),
+            ("/skip/c", page("<main>Page C text.</main>")),
+            ("/sitemap.xml", sitemap("/sitemap.xml")),
+            ("/pages.xml", sitemap("/pages.xml")),
+            ("/old.xml", sitemap("/old.xml")),
+        ];
+        if let Some(robots) = robots {
+            routes.push(("/robots.txt", Reply::ok(robots)));
+        }
+        routes
+    });
+
+    server.url("/")
+}
+
+fn site_sitemap(path: &str, base: &str) -> String {
+    let entry = |tag: &str, path: &str, lastmod: Option<&str>| {
+        let lastmod = lastmod
+            .map(|date| format!("<lastmod>{}</lastmod>", date))
+            .unwrap_or_default();
+        format!("<{tag}><loc>{base}{path}</loc>{lastmod}</{tag}>")
+    };
+    let (root, entries) = match path {
+        "/sitemap.xml" => (
+            "sitemapindex",
+            [
+                entry("sitemap", "/pages.xml", Some("2024-06-01")),
+                entry("sitemap", "/old.xml", Some("2020-01-01")),
+                entry("sitemap", "/missing.xml", None),
+            ]
+            .concat(),
+        ),
+        "/pages.xml" => (
+            "urlset",
+            [
+                entry("url", "/a", Some("2024-05-01T12:00:00+00:00")),
+                entry("url", "/b", Some("2019-01-01")),
+                entry("url", "/skip/c", Some("2024-05-01")),
+                entry("url", "/a2", None),
+                entry("url", "/a", Some("2024-05-01")),
+            ]
+            .concat(),
+        ),
+        _ => ("urlset", entry("url", "/b", Some("2020-01-01"))),
+    };
+    format!(
+        "<?xml version=\"1.0\"?><{root} xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">{entries}</{root}>"
+    )
+}
+
+async fn crawled_pages(scraper: BasicScraper) -> Vec<String> {
+    scraper
+        .fetch_documents()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|document| document.text.trim().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_basic_scraper_crawls_same_site_links() {
+    let url = serve_site(None);
+    let scraper = BasicScraper::new(url.clone(), "main".to_string()).with_max_depth(1);
+
+    let documents = scraper.fetch_documents().await.unwrap();
+    let texts: Vec<&str> = documents.iter().map(|d| d.text.trim()).collect();
+
+    assert_eq!(
+        texts,
+        [
+            "Home page text.",
+            "Page A text.",
+            "Page B text.",
+            "Page C text."
+        ]
+    );
+    assert_eq!(documents[0].title.as_deref(), Some("Home"));
+    assert_eq!(
+        documents[2].source.as_deref(),
+        Some(format!("{}b", url).as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_basic_scraper_crawl_filters_and_limits() {
+    let url = serve_site(None);
+
+    let filtered = BasicScraper::new(url.clone(), "main".to_string())
+        .with_max_depth(2)
+        .with_link_pattern("/a")
+        .unwrap();
+    assert_eq!(
+        crawled_pages(filtered).await,
+        ["Home page text.", "Page A text.", "Page A2 text."]
+    );
+
+    let limited = BasicScraper::new(url, "main".to_string())
+        .with_max_depth(2)
+        .with_max_pages(2);
+    assert_eq!(
+        crawled_pages(limited).await,
+        ["Home page text.", "Page A text."]
+    );
+}
+
+#[tokio::test]
+async fn test_basic_scraper_failed_pages_do_not_count_toward_max_pages() {
+    let server = TestServer::start(vec![
+        (
+            "/",
+            Reply::ok(
+                "<main>Home page text.</main>\
+                 <a href=\"/missing\">Gone</a><a href=\"/x\">X</a><a href=\"/y\">Y</a>",
+            ),
+        ),
+        ("/x", Reply::ok("<main>Page X text.</main>")),
+        ("/y", Reply::ok("<main>Page Y text.</main>")),
+    ]);
+    let scraper = BasicScraper::new(server.url("/"), "main".to_string())
+        .with_max_depth(1)
+        .with_max_pages(3)
+        .with_request_policy(RequestPolicy {
+            max_retries: 0,
+            ..RequestPolicy::default()
+        });
+
+    assert_eq!(
+        crawled_pages(scraper).await,
+        ["Home page text.", "Page X text.", "Page Y text."]
+    );
+}
+
+const ROBOTS: &str = "User-agent: *\nDisallow: /\n\n\
+                      User-agent: text-gen-ngram\nDisallow: /skip/\nCrawl-delay: 0.1\n";
+
+#[tokio::test]
+async fn test_basic_scraper_honours_robots_txt() {
+    let url = serve_site(Some(ROBOTS));
+    let scraper = BasicScraper::new(url, "main".to_string()).with_max_depth(1);
+
+    let started = Instant::now();
+    let pages = crawled_pages(scraper).await;
+
+    assert_eq!(pages, ["Home page text.", "Page A text.", "Page B text."]);
+    // Three page requests, spaced by the crawl delay
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_basic_scraper_fails_on_disallowed_start_page() {
+    let url = serve_site(Some(ROBOTS));
+    let scraper = BasicScraper::new(format!("{}skip/c", url), "main".to_string());
+
+    let error = scraper.fetch_documents().await.unwrap_err();
+
+    assert!(error.to_string().contains("disallowed by robots.txt"));
+}
+
+#[tokio::test]
+async fn test_basic_scraper_retries_unavailable_robots_txt() {
+    let server = TestServer::start(vec![
+        ("/robots.txt", Reply::status(503)),
+        ("/robots.txt", Reply::ok(ROBOTS)),
+        ("/", Reply::ok("<main>Home page text.</main>")),
+    ]);
+    let scraper =
+        BasicScraper::new(server.url("/"), "main".to_string()).with_request_policy(RequestPolicy {
+            max_retries: 0,
+            ..RequestPolicy::default()
+        });
+
+    let error = scraper.fetch_documents().await.unwrap_err();
+    assert!(error.to_string().contains("robots.txt is unavailable"));
+
+    // The failure is not remembered, so the next request fetches robots.txt again
+    assert_eq!(crawled_pages(scraper).await, ["Home page text."]);
+    assert_eq!(server.hits("/robots.txt"), 2);
+}
+
+#[tokio::test]
+async fn test_basic_scraper_can_ignore_robots_txt() {
+    let server = TestServer::start(vec![
+        ("/robots.txt", Reply::ok("User-agent: *\nDisallow: /\n")),
+        ("/", Reply::ok("<main>Home page text.</main>")),
+    ]);
+    let config = ScraperConfig {
+        scraper_type: "basic".to_string(),
+        settings: json!({
+            "url": server.url("/"),
+            "selector": "main",
+            "ignore_robots": true,
+        }),
+    };
+
+    let scraper = load_scraper_from_config(&config).unwrap();
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    assert_eq!(documents[0].text.trim(), "Home page text.");
+    assert_eq!(server.hits("/robots.txt"), 0);
+}
+
+#[tokio::test]
+async fn test_basic_scraper_reads_sitemap_index() {
+    let url = serve_site(None);
+    let since = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let scraper = BasicScraper::new(String::new(), "main".to_string())
+        .with_sitemap(format!("{}sitemap.xml", url))
+        .with_modified_since(since);
+
+    assert_eq!(
+        crawled_pages(scraper).await,
+        ["Page A text.", "Page C text.", "Page A2 text."]
+    );
+}
+
+#[tokio::test]
+async fn test_basic_scraper_sitemap_from_config() {
+    let url = serve_site(None);
+    let config = ScraperConfig {
+        scraper_type: "basic".to_string(),
+        settings: json!({
+            "selector": "main",
+            "sitemap": format!("{}pages.xml", url),
+            "link_pattern": "/(b|skip/)",
+            "max_pages": 1,
+        }),
+    };
+
+    let scraper = load_scraper_from_config(&config).unwrap();
+    let documents = scraper.fetch_documents().await.unwrap();
+
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].text.trim(), "Page B text.");
+    assert_eq!(
+        documents[0].source.as_deref(),
+        Some(format!("{}b", url).as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_basic_scraper_fails_on_missing_sitemap() {
+    let url = serve_site(None);
+    let scraper = BasicScraper::new(String::new(), "main".to_string())
+        .with_sitemap(format!("{}missing.xml", url));
+
+    let error = scraper.fetch_documents().await.unwrap_err();
+
+    assert!(error.to_string().contains("HTTP 404"));
+}
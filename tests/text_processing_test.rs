@@ -1,5 +1,7 @@
 use text_gen_ngram::text::processing::tokenize_large_text;
-use text_gen_ngram::text::{TokenizerOptions, normalize_text, tokenize};
+use text_gen_ngram::text::{
+    StemmingLanguage, TokenizerOptions, detokenize, normalize_text, tokenize,
+};
 
 #[test]
 fn test_normalize_text() {
@@ -9,7 +11,11 @@ fn test_normalize_text() {
 
     let text = "Hello\n\nworld\t\ttest";
     let normalized = normalize_text(text);
-    assert_eq!(normalized, "Hello world test");
+    assert_eq!(normalized, "Hello\n\nworld test");
+
+    let text = "Hello\nworld\n# Heading\nbody  text\n \n\nend";
+    let normalized = normalize_text(text);
+    assert_eq!(normalized, "Hello world\n\n# Heading\n\nbody text\n\nend");
 
     let text = "";
     let normalized = normalize_text(text);
@@ -126,3 +132,119 @@ fn test_tokenize_large_text() {
     let tokens = result.unwrap();
     assert_eq!(tokens, tokenize(small_text, &options));
 }
+
+#[test]
+fn test_tokenize_large_text_keeps_characters_whole() {
+    // The 100 kB chunk limits fall in the middle of a three-byte character
+    let word = "€".repeat(40);
+    let large_text = format!("{} ää ", word).repeat(8_200);
+    let options = TokenizerOptions::default();
+
+    let tokens = tokenize_large_text(&large_text, &options).unwrap();
+
+    assert_eq!(tokens.len(), 2 * 8_200);
+    assert!(tokens.iter().all(|token| *token == word || token == "ää"));
+}
+
+#[test]
+fn test_detokenize_round_trip() {
+    let options = TokenizerOptions {
+        lowercase: false,
+        ..Default::default()
+    };
+
+    let samples = [
+        "I don't think John's car is \"fast\", is it? They'll say it's rock 'n' roll (mostly).",
+        "She said 'hello' and I'm sure we'd have gone, but you're late.",
+        "It isn't; we've tried [twice]: it won't work!",
+        "DON'T shout, it’s fine.",
+        "The dogs' bowls and James' car are here; 'Tis late, 'twas said.",
+        "She said 'yes' to James' dog, so get 'em now.",
+    ];
+
+    for sample in samples {
+        let tokens = tokenize(sample, &options);
+        assert_eq!(detokenize(&tokens), sample, "tokens: {:?}", tokens);
+    }
+}
+
+#[test]
+fn test_detokenize_drops_sentence_markers() {
+    let options = TokenizerOptions::default();
+    let tokens = tokenize("First one. Second one.", &options);
+
+    assert_eq!(detokenize(&tokens), "first one. second one.");
+}
+
+#[test]
+fn test_tokenize_paragraphs_and_sections() {
+    let text = normalize_text("# Rust\nRust is fast.\n\nIt is also safe.");
+    let options = TokenizerOptions::default();
+    let tokens = tokenize(&text, &options);
+
+    let expected = [
+        "<section>",
+        "rust",
+        "<paragraph>",
+        "rust",
+        "is",
+        "fast.",
+        "<sentence>",
+        "<paragraph>",
+        "it",
+        "is",
+        "also",
+        "safe",
+        ".",
+    ];
+    assert_eq!(tokens, expected.map(String::from));
+
+    let options = TokenizerOptions {
+        preserve_paragraphs: false,
+        ..Default::default()
+    };
+    let tokens = tokenize(&text, &options);
+    assert!(
+        !tokens
+            .iter()
+            .any(|t| t == "<paragraph>" || t == "<section>")
+    );
+    assert_eq!(tokens[0], "#");
+}
+
+#[test]
+fn test_structure_round_trip() {
+    let options = TokenizerOptions {
+        lowercase: false,
+        ..Default::default()
+    };
+    let text = normalize_text("# Title\n\nFirst paragraph here.\n\n# Next\nSecond one, too.");
+
+    let tokens = tokenize(&text, &options);
+    assert_eq!(detokenize(&tokens), text);
+}
+
+#[test]
+fn test_stemming_reduces_vocabulary() {
+    let options = TokenizerOptions {
+        stemming: Some(StemmingLanguage::English),
+        ..Default::default()
+    };
+    let tokens = tokenize("The dogs were running. A dog runs!", &options);
+
+    assert_eq!(
+        tokens,
+        [
+            "the",
+            "dog",
+            "were",
+            "run.",
+            "<sentence>",
+            "a",
+            "dog",
+            "run",
+            "!"
+        ]
+        .map(String::from)
+    );
+}
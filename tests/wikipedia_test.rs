@@ -0,0 +1,168 @@
+use serde_json::{Value, json};
+use text_gen_ngram::scrapers::providers::wikipedia::{
+    DEFAULT_SKIPPED_SECTIONS, WikipediaMode, WikipediaScraper, parse_article,
+    parse_category_members, parse_links, parse_summary,
+};
+
+fn fixture(json: &str) -> Value {
+    serde_json::from_str(json).unwrap()
+}
+
+fn default_skipped() -> Vec<String> {
+    DEFAULT_SKIPPED_SECTIONS
+        .iter()
+        .map(|section| section.to_string())
+        .collect()
+}
+
+#[test]
+fn test_parse_summary() {
+    let data = fixture(include_str!("fixtures/wikipedia_summary.json"));
+
+    let document = parse_summary(&data, "Ferris_wheel", "en").unwrap();
+
+    assert_eq!(document.title.as_deref(), Some("Ferris wheel"));
+    assert_eq!(document.language.as_deref(), Some("en"));
+    assert_eq!(
+        document.source.as_deref(),
+        Some("https://en.wikipedia.org/wiki/Ferris_wheel")
+    );
+    assert!(
+        document
+            .text
+            .starts_with("A Ferris wheel is an amusement ride")
+    );
+}
+
+#[test]
+fn test_parse_summary_language() {
+    let mut data = fixture(include_str!("fixtures/wikipedia_summary_de.json"));
+
+    let document = parse_summary(&data, "Riesenrad", "en").unwrap();
+    assert_eq!(document.title.as_deref(), Some("Riesenrad"));
+    assert_eq!(document.language.as_deref(), Some("de"));
+
+    // Without a lang field the configured language edition is used
+    data.as_object_mut().unwrap().remove("lang");
+    let document = parse_summary(&data, "Riesenrad", "de").unwrap();
+    assert_eq!(document.language.as_deref(), Some("de"));
+}
+
+#[test]
+fn test_parse_article_keeps_headings_and_skips_sections() {
+    let data = fixture(include_str!("fixtures/wikipedia_article.json"));
+
+    let document = parse_article(&data, "Ferris_wheel", "en", &default_skipped()).unwrap();
+
+    assert_eq!(document.title.as_deref(), Some("Ferris wheel"));
+    assert_eq!(document.language.as_deref(), Some("en"));
+    assert_eq!(
+        document.text,
+        "A Ferris wheel is an amusement ride consisting of a rotating upright wheel.\n\n\
+         The original Ferris wheel was designed by George Washington Gale Ferris Jr.\n\n\
+         ## History\n\n\
+         ### Pleasure wheels\n\n\
+         Pleasure wheels, whose passengers rode in chairs, have been recorded since the 17th century.\n\n\
+         ### Chicago 1893\n\n\
+         The first Ferris wheel opened at the World's Columbian Exposition.\n\n\
+         It carried 2,160 people at a time.\n\n\
+         ## Design\n\n\
+         A modern wheel is supported by a tower."
+    );
+}
+
+#[test]
+fn test_parse_article_language() {
+    let mut data = fixture(include_str!("fixtures/wikipedia_article.json"));
+
+    // Without a pagelanguage field the configured language edition is used
+    data["query"]["pages"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("pagelanguage");
+    let document = parse_article(&data, "Ferris_wheel", "de", &default_skipped()).unwrap();
+    assert_eq!(document.language.as_deref(), Some("de"));
+}
+
+#[test]
+fn test_parse_article_with_custom_skip_list() {
+    let data = fixture(include_str!("fixtures/wikipedia_article.json"));
+
+    let document = parse_article(&data, "Ferris_wheel", "en", &["history".to_string()]).unwrap();
+
+    assert!(!document.text.contains("Pleasure wheels"));
+    assert!(
+        document
+            .text
+            .contains("## See also\n\nList of Ferris wheels")
+    );
+    assert!(document.text.contains("### Similar rides\n\nParis Wheel"));
+    assert!(!document.text.contains("## References"));
+}
+
+#[test]
+fn test_parse_missing_article() {
+    let data = fixture(include_str!("fixtures/wikipedia_missing.json"));
+
+    let result = parse_article(&data, "No such article here", "en", &default_skipped());
+
+    assert!(result.unwrap_err().to_string().contains("no article"));
+}
+
+#[test]
+fn test_mode_from_name() {
+    assert_eq!(
+        WikipediaMode::from_name("full").unwrap(),
+        WikipediaMode::FullArticle
+    );
+    assert_eq!(
+        WikipediaMode::from_name("Summary").unwrap(),
+        WikipediaMode::Summary
+    );
+    assert!(WikipediaMode::from_name("everything").is_err());
+}
+
+#[test]
+fn test_from_config_rejects_invalid_mode() {
+    let settings = json!({ "topics": ["Ferris_wheel"], "mode": "everything" });
+
+    let Err(error) = WikipediaScraper::from_config(&settings) else {
+        panic!("mode 'everything' was accepted");
+    };
+    assert!(error.to_string().contains("everything"), "{}", error);
+}
+
+#[test]
+fn test_parse_category_members() {
+    let data = fixture(include_str!("fixtures/wikipedia_category_members.json"));
+
+    let batch = parse_category_members(&data);
+
+    assert_eq!(batch.articles, ["Fysiikka", "Energia"]);
+    assert_eq!(batch.subcategories, ["Luokka:Mekaniikka"]);
+    assert_eq!(
+        batch.continuation.as_deref(),
+        Some("page|4b494e4554494b4b41|21593")
+    );
+}
+
+#[test]
+fn test_parse_links() {
+    let data = fixture(include_str!("fixtures/wikipedia_links.json"));
+
+    let batch = parse_links(&data);
+
+    assert_eq!(
+        batch.articles,
+        [
+            "Amusement ride",
+            "George Washington Gale Ferris Jr.",
+            "London Eye"
+        ]
+    );
+    assert!(batch.subcategories.is_empty());
+    assert_eq!(
+        batch.continuation.as_deref(),
+        Some("11287|0|Observation_wheel")
+    );
+}